@@ -2,6 +2,8 @@
 mod util;
 
 use criterion::{criterion_group, criterion_main, Criterion};
+use nanokit::count_bits::{bits_histogram, max_bits_needed, BitsNeeded};
+use nanokit::popcount::{count_ones_slice, hamming_distance};
 use std::hint::black_box;
 
 fn fibonacci(n: u64) -> u64 {
@@ -16,10 +18,83 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("fib 20", |b| b.iter(|| fibonacci(black_box(20))));
 }
 
+fn slice_bits_needed_benchmark(c: &mut Criterion) {
+    let values: Vec<u32> = (0..1_000_000u32)
+        .map(|x| x.wrapping_mul(2_654_435_761) >> (x % 32))
+        .collect();
+
+    let mut group = c.benchmark_group("max_bits_needed 1M u32");
+    group.bench_function("naive", |b| {
+        b.iter(|| {
+            black_box(&values)
+                .iter()
+                .map(|value| value.bits_needed_to_store())
+                .max()
+        })
+    });
+    group.bench_function("or-reduction", |b| {
+        b.iter(|| max_bits_needed(black_box(&values)))
+    });
+    group.finish();
+
+    // Mixed widths, and the common case of values that mostly share a width.
+    let narrow: Vec<u32> = values.iter().map(|value| 512 + value % 512).collect();
+    for (name, values) in [("mixed", &values), ("narrow", &narrow)] {
+        let mut group = c.benchmark_group(format!("bits_histogram 1M u32 {name}"));
+        group.bench_function("naive", |b| {
+            b.iter(|| {
+                let mut histogram = [0usize; 33];
+                for value in black_box(values) {
+                    histogram[value.bits_needed_to_store() as usize] += 1;
+                }
+                histogram
+            })
+        });
+        group.bench_function("chunked", |b| b.iter(|| bits_histogram(black_box(values))));
+        group.finish();
+    }
+}
+
+fn popcount_benchmark(c: &mut Criterion) {
+    let a: Vec<u64> = (0..100_000u64)
+        .map(|x| x.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .collect();
+    let b: Vec<u64> = a.iter().map(|word| word.rotate_left(17)).collect();
+
+    let mut group = c.benchmark_group("count_ones 100K u64");
+    group.bench_function("naive", |bench| {
+        bench.iter(|| {
+            black_box(&a)
+                .iter()
+                .map(|word| word.count_ones() as u64)
+                .sum::<u64>()
+        })
+    });
+    group.bench_function("count_ones_slice", |bench| {
+        bench.iter(|| count_ones_slice(black_box(&a)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("hamming_distance 100K u64");
+    group.bench_function("naive", |bench| {
+        bench.iter(|| {
+            black_box(&a)
+                .iter()
+                .zip(black_box(&b))
+                .map(|(a, b)| (a ^ b).count_ones() as u64)
+                .sum::<u64>()
+        })
+    });
+    group.bench_function("hamming_distance", |bench| {
+        bench.iter(|| hamming_distance(black_box(&a), black_box(&b)))
+    });
+    group.finish();
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default();
-    targets = criterion_benchmark
+    targets = criterion_benchmark, slice_bits_needed_benchmark, popcount_benchmark
 }
 
 criterion_main!(benches);
@@ -0,0 +1,394 @@
+use crate::string_concat::{ConcatPart, Part};
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use alloc::vec::Vec;
+use core::cell::{Cell, UnsafeCell};
+use core::fmt;
+use core::ptr::NonNull;
+
+/// Size of the first chunk allocated by an [`Arena`] created with [`Arena::new`].
+const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+/// A bump allocator for strings.
+///
+/// Strings are copied into large chunks of memory and returned as `&str` borrowed from the arena,
+/// so building many short-lived strings costs a pointer bump each, rather than a heap allocation.
+/// When a chunk runs out of space, a new chunk at least twice as large is allocated;
+/// previously returned strings stay where they are.
+///
+/// All memory is freed when the arena is dropped, or reused after [`Arena::reset`].
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::arena::Arena;
+/// let arena = Arena::new();
+/// let namespace = "std";
+/// let path = arena.concat_3(namespace, "::", "vec");
+/// let other = arena.concat_2(path, "::Vec");
+/// assert_eq!(path, "std::vec");
+/// assert_eq!(other, "std::vec::Vec");
+/// ```
+pub struct Arena {
+    /// Every chunk allocated so far. The last chunk is the one being bumped into.
+    chunks: UnsafeCell<Vec<Chunk>>,
+    /// Next free byte in the last chunk.
+    cursor: Cell<*mut u8>,
+    /// Number of free bytes after `cursor`.
+    remaining: Cell<usize>,
+    /// Size of the first chunk.
+    first_chunk_size: usize,
+}
+
+struct Chunk {
+    ptr: NonNull<u8>,
+    capacity: usize,
+}
+
+// SAFETY: The arena owns all of its chunks, and is not `Sync`.
+unsafe impl Send for Arena {}
+
+impl Arena {
+    /// Creates an empty arena. No memory is allocated until the first string is added.
+    pub const fn new() -> Self {
+        Self::with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates an empty arena whose first chunk will be `chunk_size` bytes large.
+    ///
+    /// No memory is allocated until the first string is added.
+    pub const fn with_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            chunks: UnsafeCell::new(Vec::new()),
+            cursor: Cell::new(NonNull::dangling().as_ptr()),
+            remaining: Cell::new(0),
+            first_chunk_size: if chunk_size == 0 { 1 } else { chunk_size },
+        }
+    }
+
+    /// Copies `text` into the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::arena::Arena;
+    /// let arena = Arena::new();
+    /// let text = arena.alloc_str("hello");
+    /// assert_eq!(text, "hello");
+    /// ```
+    pub fn alloc_str<S>(&self, text: S) -> &str
+    where
+        S: ConcatPart,
+    {
+        let text = text.to_part();
+        unsafe {
+            let dst = self.alloc_bytes(text.len());
+            text.write(dst);
+            str_from_raw_parts(dst, text.len())
+        }
+    }
+
+    /// Concatenates two strings into the arena.
+    ///
+    /// Takes any type that implements [`ConcatPart`], just like [`concat_2`], but the result
+    /// is allocated in the arena with its exact length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::arena::Arena;
+    /// let arena = Arena::new();
+    /// assert_eq!(arena.concat_2("Hello, ", "world!"), "Hello, world!");
+    /// ```
+    ///
+    /// [`concat_2`]: crate::string_concat::concat_2
+    #[cfg_attr(feature = "no-inline-concat", inline(never))]
+    pub fn concat_2<S1, S2>(&self, s1: S1, s2: S2) -> &str
+    where
+        S1: ConcatPart,
+        S2: ConcatPart,
+    {
+        self.concat_parts(&[s1.to_part(), s2.to_part()])
+    }
+
+    /// Concatenates three strings into the arena.
+    ///
+    /// See [`Arena::concat_2`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::arena::Arena;
+    /// let arena = Arena::new();
+    /// assert_eq!(arena.concat_3("core", "::", "mem"), "core::mem");
+    /// ```
+    #[cfg_attr(feature = "no-inline-concat", inline(never))]
+    pub fn concat_3<S1, S2, S3>(&self, s1: S1, s2: S2, s3: S3) -> &str
+    where
+        S1: ConcatPart,
+        S2: ConcatPart,
+        S3: ConcatPart,
+    {
+        self.concat_parts(&[s1.to_part(), s2.to_part(), s3.to_part()])
+    }
+
+    /// Concatenates four strings into the arena.
+    ///
+    /// See [`Arena::concat_2`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::arena::Arena;
+    /// let arena = Arena::new();
+    /// assert_eq!(arena.concat_4("a", "b", "c", "d"), "abcd");
+    /// ```
+    #[cfg_attr(feature = "no-inline-concat", inline(never))]
+    pub fn concat_4<S1, S2, S3, S4>(&self, s1: S1, s2: S2, s3: S3, s4: S4) -> &str
+    where
+        S1: ConcatPart,
+        S2: ConcatPart,
+        S3: ConcatPart,
+        S4: ConcatPart,
+    {
+        self.concat_parts(&[s1.to_part(), s2.to_part(), s3.to_part(), s4.to_part()])
+    }
+
+    /// Concatenates five strings into the arena.
+    ///
+    /// See [`Arena::concat_2`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::arena::Arena;
+    /// let arena = Arena::new();
+    /// assert_eq!(arena.concat_5("a", "b", "c", "d", "e"), "abcde");
+    /// ```
+    #[cfg_attr(feature = "no-inline-concat", inline(never))]
+    pub fn concat_5<S1, S2, S3, S4, S5>(&self, s1: S1, s2: S2, s3: S3, s4: S4, s5: S5) -> &str
+    where
+        S1: ConcatPart,
+        S2: ConcatPart,
+        S3: ConcatPart,
+        S4: ConcatPart,
+        S5: ConcatPart,
+    {
+        self.concat_parts(&[
+            s1.to_part(),
+            s2.to_part(),
+            s3.to_part(),
+            s4.to_part(),
+            s5.to_part(),
+        ])
+    }
+
+    /// Frees all strings in the arena, keeping the most recent (largest) chunk for reuse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::arena::Arena;
+    /// let mut arena = Arena::new();
+    /// arena.alloc_str("temporary");
+    /// let capacity = arena.capacity();
+    ///
+    /// arena.reset();
+    /// arena.alloc_str("reused");
+    /// assert_eq!(arena.capacity(), capacity);
+    /// ```
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        let Some(last) = chunks.pop() else {
+            return;
+        };
+
+        for chunk in chunks.drain(..) {
+            chunk.free();
+        }
+
+        self.cursor.set(last.ptr.as_ptr());
+        self.remaining.set(last.capacity);
+        chunks.push(last);
+    }
+
+    /// Returns the total number of bytes allocated for chunks.
+    pub fn capacity(&self) -> usize {
+        // SAFETY: `chunks` is only mutated by `grow`, which never runs while this borrow is live.
+        unsafe { (*self.chunks.get()).iter().map(|c| c.capacity).sum() }
+    }
+
+    /// Copies the parts into a single allocation of their exact total length.
+    #[inline(always)]
+    fn concat_parts(&self, parts: &[Part<'_>]) -> &str {
+        let total_length = parts.iter().map(|p| p.len()).sum();
+        unsafe {
+            let dst = self.alloc_bytes(total_length);
+            let mut pos = 0;
+            for part in parts {
+                part.write(dst.add(pos));
+                pos += part.len();
+            }
+            str_from_raw_parts(dst, total_length)
+        }
+    }
+
+    /// Reserves `len` bytes in the arena, returning a pointer to the first one.
+    #[inline(always)]
+    fn alloc_bytes(&self, len: usize) -> *mut u8 {
+        if len > self.remaining.get() {
+            self.grow(len);
+        }
+
+        let ptr = self.cursor.get();
+        // SAFETY: `len` bytes after `cursor` are free in the current chunk.
+        self.cursor.set(unsafe { ptr.add(len) });
+        self.remaining.set(self.remaining.get() - len);
+        ptr
+    }
+
+    /// Allocates a new chunk with room for at least `len` bytes, and makes it current.
+    #[cold]
+    #[inline(never)]
+    fn grow(&self, len: usize) {
+        // SAFETY: The arena is not `Sync`, and no other reference to `chunks` is live.
+        let chunks = unsafe { &mut *self.chunks.get() };
+        let capacity = chunks
+            .last()
+            .map_or(self.first_chunk_size, |c| c.capacity.saturating_mul(2))
+            .max(len);
+
+        let chunk = Chunk::new(capacity);
+        self.cursor.set(chunk.ptr.as_ptr());
+        self.remaining.set(chunk.capacity);
+        chunks.push(chunk);
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        for chunk in self.chunks.get_mut().drain(..) {
+            chunk.free();
+        }
+    }
+}
+
+impl fmt::Debug for Arena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena")
+            .field("capacity", &self.capacity())
+            .field("remaining", &self.remaining.get())
+            .finish()
+    }
+}
+
+impl Chunk {
+    fn new(capacity: usize) -> Self {
+        let layout = Layout::array::<u8>(capacity).expect("capacity overflow");
+        // SAFETY: `capacity` is never zero.
+        let ptr = unsafe { alloc(layout) };
+        match NonNull::new(ptr) {
+            Some(ptr) => Self { ptr, capacity },
+            None => handle_alloc_error(layout),
+        }
+    }
+
+    fn free(self) {
+        // SAFETY: Allocated in `Chunk::new` with the same layout.
+        unsafe {
+            dealloc(
+                self.ptr.as_ptr(),
+                Layout::from_size_align_unchecked(self.capacity, 1),
+            )
+        };
+    }
+}
+
+/// # Safety
+///
+/// `len` bytes at `ptr` must be initialized with valid UTF-8, and outlive the returned `str`.
+#[inline(always)]
+unsafe fn str_from_raw_parts<'a>(ptr: *const u8, len: usize) -> &'a str {
+    core::str::from_utf8_unchecked(core::slice::from_raw_parts(ptr, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string_escape::JsonEscaped;
+    use alloc::format;
+    use alloc::string::String;
+
+    #[test]
+    fn test_concat_n() {
+        let arena = Arena::new();
+        let owned = String::from("b");
+        assert_eq!(arena.concat_2("a", &owned), "ab");
+        assert_eq!(arena.concat_3("a", owned.as_str(), "c"), "abc");
+        assert_eq!(arena.concat_4("a", "b", "c", "d"), "abcd");
+        assert_eq!(arena.concat_5("a", "b", "c", "d", owned), "abcdb");
+        assert_eq!(arena.concat_2("\"", JsonEscaped("\"")), "\"\\\"");
+    }
+
+    #[test]
+    fn test_empty_strings() {
+        let arena = Arena::new();
+        assert_eq!(arena.alloc_str(""), "");
+        assert_eq!(arena.concat_3("", "", ""), "");
+        assert_eq!(arena.capacity(), 0);
+    }
+
+    #[test]
+    fn test_strings_survive_chunk_growth() {
+        let arena = Arena::with_chunk_size(16);
+        let strings: alloc::vec::Vec<&str> = (0..1000)
+            .map(|x| arena.concat_3("item", format!("{x}"), "!"))
+            .collect();
+
+        for (x, text) in strings.iter().enumerate() {
+            assert_eq!(*text, format!("item{x}!"));
+        }
+
+        // Chunks double in size, so only a handful are needed.
+        let chunk_count = unsafe { (*arena.chunks.get()).len() };
+        assert!(chunk_count <= 12, "Too many chunks: {chunk_count}");
+    }
+
+    #[test]
+    fn test_allocation_larger_than_chunk() {
+        let arena = Arena::with_chunk_size(4);
+        let long = "x".repeat(100);
+        assert_eq!(arena.concat_2(&long, "y").len(), 101);
+        assert_eq!(arena.alloc_str("z"), "z");
+        assert!(arena.capacity() >= 101);
+    }
+
+    #[test]
+    fn test_reset_keeps_last_chunk() {
+        let mut arena = Arena::with_chunk_size(8);
+        for _ in 0..10 {
+            arena.alloc_str("0123456789");
+        }
+
+        let last_capacity = unsafe { (*arena.chunks.get()).last().unwrap().capacity };
+        arena.reset();
+        assert_eq!(arena.capacity(), last_capacity);
+        assert_eq!(arena.remaining.get(), last_capacity);
+
+        assert_eq!(arena.concat_2("after", "reset"), "afterreset");
+        assert_eq!(arena.capacity(), last_capacity);
+    }
+
+    #[test]
+    fn test_reset_empty_arena() {
+        let mut arena = Arena::default();
+        arena.reset();
+        assert_eq!(arena.alloc_str("a"), "a");
+        assert_eq!(arena.capacity(), DEFAULT_CHUNK_SIZE);
+    }
+}
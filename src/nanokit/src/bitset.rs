@@ -0,0 +1,682 @@
+use crate::popcount::count_ones_slice;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A fixed-size set of `N * 64` bits, stored inline as `N` words.
+///
+/// `N` counts words rather than bits, so the storage size is known without const generic
+/// arithmetic. Indices at or above [`BitSet::CAPACITY`] panic.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::bitset::BitSet;
+/// let mut seen = BitSet::<2>::new();
+/// seen.set(3);
+/// seen.set(100);
+/// assert!(seen.test(100));
+/// assert_eq!(seen.count_ones(), 2);
+/// assert_eq!(seen.iter_ones().collect::<Vec<_>>(), [3, 100]);
+/// assert_eq!(seen.last_one(), Some(100));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet<const N: usize> {
+    words: [u64; N],
+}
+
+impl<const N: usize> BitSet<N> {
+    /// The number of bits in the set.
+    pub const CAPACITY: usize = N * 64;
+
+    /// Creates a set with every bit cleared.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { words: [0; N] }
+    }
+
+    /// Creates a set from its words; bit `i` is bit `i % 64` of word `i / 64`.
+    #[inline]
+    pub const fn from_words(words: [u64; N]) -> Self {
+        Self { words }
+    }
+
+    /// Returns the words backing the set.
+    #[inline]
+    pub const fn as_words(&self) -> &[u64; N] {
+        &self.words
+    }
+
+    /// Sets the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`BitSet::CAPACITY`].
+    #[inline]
+    pub fn set(&mut self, index: usize) {
+        let (word, mask) = locate(index, Self::CAPACITY);
+        self.words[word] |= mask;
+    }
+
+    /// Clears the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`BitSet::CAPACITY`].
+    #[inline]
+    pub fn clear(&mut self, index: usize) {
+        let (word, mask) = locate(index, Self::CAPACITY);
+        self.words[word] &= !mask;
+    }
+
+    /// Flips the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`BitSet::CAPACITY`].
+    #[inline]
+    pub fn toggle(&mut self, index: usize) {
+        let (word, mask) = locate(index, Self::CAPACITY);
+        self.words[word] ^= mask;
+    }
+
+    /// Returns `true` if the bit at `index` is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`BitSet::CAPACITY`].
+    #[inline]
+    pub fn test(&self, index: usize) -> bool {
+        let (word, mask) = locate(index, Self::CAPACITY);
+        self.words[word] & mask != 0
+    }
+
+    /// Clears every bit.
+    #[inline]
+    pub fn clear_all(&mut self) {
+        self.words = [0; N];
+    }
+
+    /// Returns `true` if no bit is set.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns the number of set bits.
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        count_ones_slice(&self.words) as usize
+    }
+
+    /// Returns the index of the lowest set bit, or [`None`] if no bit is set.
+    #[inline]
+    pub fn first_one(&self) -> Option<usize> {
+        first_one(&self.words)
+    }
+
+    /// Returns the index of the highest set bit, or [`None`] if no bit is set.
+    #[inline]
+    pub fn last_one(&self) -> Option<usize> {
+        last_one(&self.words)
+    }
+
+    /// Returns an iterator over the indices of the set bits, in increasing order.
+    #[inline]
+    pub fn iter_ones(&self) -> Ones<'_> {
+        Ones::new(&self.words)
+    }
+
+    /// Sets every bit that is set in `other`.
+    #[inline]
+    pub fn union_with(&mut self, other: &Self) {
+        zip_words(&mut self.words, &other.words, |a, b| a | b);
+    }
+
+    /// Clears every bit that is not set in `other`.
+    #[inline]
+    pub fn intersect_with(&mut self, other: &Self) {
+        zip_words(&mut self.words, &other.words, |a, b| a & b);
+    }
+
+    /// Clears every bit that is set in `other`.
+    #[inline]
+    pub fn difference_with(&mut self, other: &Self) {
+        zip_words(&mut self.words, &other.words, |a, b| a & !b);
+    }
+
+    /// Returns the bits set in either set.
+    #[inline]
+    pub fn union(mut self, other: &Self) -> Self {
+        self.union_with(other);
+        self
+    }
+
+    /// Returns the bits set in both sets.
+    #[inline]
+    pub fn intersection(mut self, other: &Self) -> Self {
+        self.intersect_with(other);
+        self
+    }
+
+    /// Returns the bits set in `self` but not in `other`.
+    #[inline]
+    pub fn difference(mut self, other: &Self) -> Self {
+        self.difference_with(other);
+        self
+    }
+}
+
+impl<const N: usize> Default for BitSet<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for BitSet<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter_ones()).finish()
+    }
+}
+
+/// A growable sequence of bits, stored on the heap as `u64` words.
+///
+/// Set operations between vectors of different lengths treat the missing bits of the
+/// shorter one as cleared; [`BitVec::union_with`] grows `self` to fit.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::bitset::BitVec;
+/// let mut flags = BitVec::with_len(10);
+/// flags.set(2);
+/// flags.push(true);
+/// assert_eq!(flags.len(), 11);
+/// assert_eq!(flags.iter_ones().collect::<Vec<_>>(), [2, 10]);
+///
+/// let mut other = BitVec::with_len(200);
+/// other.set(150);
+/// flags.union_with(&other);
+/// assert_eq!(flags.len(), 200);
+/// assert_eq!(flags.count_ones(), 3);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    /// Bits at or above `len` in the last word are always cleared.
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// Creates an empty vector.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Creates a vector of `len` cleared bits.
+    #[inline]
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            words: alloc::vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// Creates a vector of `len` bits from its words; bit `i` is bit `i % 64` of word `i / 64`.
+    ///
+    /// Words past `len` are dropped, and bits past `len` in the last word are cleared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `words` holds fewer than `len` bits.
+    pub fn from_words(words: &[u64], len: usize) -> Self {
+        assert!(len <= words.len() * 64, "not enough words for {len} bits");
+        let mut result = Self {
+            words: words[..len.div_ceil(64)].to_vec(),
+            len,
+        };
+        result.clear_unused();
+        result
+    }
+
+    /// Returns the number of bits.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no bits.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the words backing the vector. Bits past [`BitVec::len`] are cleared.
+    #[inline]
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the words backing the vector, consuming it.
+    #[inline]
+    pub(crate) fn into_words(self) -> Vec<u64> {
+        self.words
+    }
+
+    /// Appends a bit.
+    #[inline]
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.words[self.len / 64] |= (value as u64) << (self.len % 64);
+        self.len += 1;
+    }
+
+    /// Resizes the vector to `len` bits, filling any new bits with `value`.
+    pub fn resize(&mut self, len: usize, value: bool) {
+        let fill = if value { u64::MAX } else { 0 };
+        if len > self.len && !self.len.is_multiple_of(64) {
+            // Fill the rest of the current last word before adding new ones.
+            *self.words.last_mut().unwrap() |= fill << (self.len % 64);
+        }
+        self.words.resize(len.div_ceil(64), fill);
+        self.len = len;
+        self.clear_unused();
+    }
+
+    /// Returns the bit at `index`, or [`None`] if it is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.test(index))
+    }
+
+    /// Sets the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn set(&mut self, index: usize) {
+        let (word, mask) = locate(index, self.len);
+        self.words[word] |= mask;
+    }
+
+    /// Clears the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn clear(&mut self, index: usize) {
+        let (word, mask) = locate(index, self.len);
+        self.words[word] &= !mask;
+    }
+
+    /// Flips the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn toggle(&mut self, index: usize) {
+        let (word, mask) = locate(index, self.len);
+        self.words[word] ^= mask;
+    }
+
+    /// Returns `true` if the bit at `index` is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn test(&self, index: usize) -> bool {
+        let (word, mask) = locate(index, self.len);
+        self.words[word] & mask != 0
+    }
+
+    /// Clears every bit, keeping the length.
+    #[inline]
+    pub fn clear_all(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Returns the number of set bits.
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        count_ones_slice(&self.words) as usize
+    }
+
+    /// Returns the index of the lowest set bit, or [`None`] if no bit is set.
+    #[inline]
+    pub fn first_one(&self) -> Option<usize> {
+        first_one(&self.words)
+    }
+
+    /// Returns the index of the highest set bit, or [`None`] if no bit is set.
+    #[inline]
+    pub fn last_one(&self) -> Option<usize> {
+        last_one(&self.words)
+    }
+
+    /// Returns an iterator over the indices of the set bits, in increasing order.
+    #[inline]
+    pub fn iter_ones(&self) -> Ones<'_> {
+        Ones::new(&self.words)
+    }
+
+    /// Sets every bit that is set in `other`, growing `self` to `other`'s length if it is
+    /// shorter.
+    pub fn union_with(&mut self, other: &BitVec) {
+        if other.len > self.len {
+            self.resize(other.len, false);
+        }
+        zip_words(&mut self.words, &other.words, |a, b| a | b);
+    }
+
+    /// Clears every bit that is not set in `other`.
+    pub fn intersect_with(&mut self, other: &BitVec) {
+        let shared = zip_words(&mut self.words, &other.words, |a, b| a & b);
+        self.words[shared..].fill(0);
+    }
+
+    /// Clears every bit that is set in `other`.
+    pub fn difference_with(&mut self, other: &BitVec) {
+        zip_words(&mut self.words, &other.words, |a, b| a & !b);
+    }
+
+    /// Clears the bits past `len` in the last word.
+    #[inline]
+    fn clear_unused(&mut self) {
+        if !self.len.is_multiple_of(64) {
+            *self.words.last_mut().unwrap() &= u64::MAX >> (64 - self.len % 64);
+        }
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (0..self.len).try_for_each(|x| f.write_str(if self.test(x) { "1" } else { "0" }))
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut result = BitVec::new();
+        for value in iter {
+            result.push(value);
+        }
+        result
+    }
+}
+
+/// An iterator over the indices of the set bits of a [`BitSet`] or [`BitVec`], created by
+/// their `iter_ones` methods.
+#[derive(Debug, Clone)]
+pub struct Ones<'a> {
+    words: &'a [u64],
+    /// Index of the first bit of `current`.
+    base: usize,
+    /// The bits of the current word that have not been returned yet.
+    current: u64,
+}
+
+impl<'a> Ones<'a> {
+    #[inline]
+    fn new(words: &'a [u64]) -> Self {
+        match words.split_first() {
+            Some((&first, rest)) => Self {
+                words: rest,
+                base: 0,
+                current: first,
+            },
+            None => Self {
+                words,
+                base: 0,
+                current: 0,
+            },
+        }
+    }
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            let (&next, rest) = self.words.split_first()?;
+            self.words = rest;
+            self.base += 64;
+            self.current = next;
+        }
+
+        let index = self.base + self.current.trailing_zeros() as usize;
+        // Clear the lowest set bit.
+        self.current &= self.current - 1;
+        Some(index)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.current.count_ones() as usize + count_ones_slice(self.words) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Ones<'_> {}
+
+/// Returns the word index and bit mask of `index`, checking it against `len`.
+#[inline(always)]
+fn locate(index: usize, len: usize) -> (usize, u64) {
+    assert!(
+        index < len,
+        "bit index {index} out of bounds for length {len}"
+    );
+    (index / 64, 1 << (index % 64))
+}
+
+#[inline]
+fn first_one(words: &[u64]) -> Option<usize> {
+    let x = words.iter().position(|&word| word != 0)?;
+    Some(x * 64 + words[x].trailing_zeros() as usize)
+}
+
+#[inline]
+fn last_one(words: &[u64]) -> Option<usize> {
+    let x = words.iter().rposition(|&word| word != 0)?;
+    Some(x * 64 + 63 - words[x].leading_zeros() as usize)
+}
+
+/// Combines each word of `dst` with the word of `src` at the same index, and returns the
+/// number of words combined.
+#[inline(always)]
+fn zip_words(dst: &mut [u64], src: &[u64], op: impl Fn(u64, u64) -> u64) -> usize {
+    for (a, &b) in dst.iter_mut().zip(src) {
+        *a = op(*a, b);
+    }
+    dst.len().min(src.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::next_random;
+    use alloc::collections::BTreeSet;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[])]
+    #[case(&[0])]
+    #[case(&[63, 64])]
+    #[case(&[1, 5, 127, 128, 255])]
+    fn test_bitset_iter_ones(#[case] indices: &[usize]) {
+        let mut set = BitSet::<4>::new();
+        for &index in indices {
+            set.set(index);
+        }
+        assert!(
+            set.iter_ones().eq(indices.iter().copied()),
+            "Failed for input: {indices:?}"
+        );
+        assert_eq!(set.iter_ones().len(), indices.len());
+        assert_eq!(set.count_ones(), indices.len());
+        assert_eq!(set.first_one(), indices.first().copied());
+        assert_eq!(set.last_one(), indices.last().copied());
+        assert_eq!(set.is_empty(), indices.is_empty());
+    }
+
+    #[test]
+    fn test_bitset_set_clear_toggle() {
+        let mut set = BitSet::<2>::default();
+        assert_eq!(BitSet::<2>::CAPACITY, 128);
+
+        set.set(70);
+        set.set(70);
+        assert!(set.test(70));
+        assert!(!set.test(69));
+        assert_eq!(set.as_words(), &[0, 1 << 6]);
+
+        set.toggle(70);
+        set.toggle(3);
+        assert!(!set.test(70));
+        assert!(set.test(3));
+
+        set.clear(3);
+        set.clear(4);
+        assert!(set.is_empty());
+
+        set.set(127);
+        set.clear_all();
+        assert_eq!(set, BitSet::new());
+    }
+
+    #[test]
+    fn test_bitset_set_operations() {
+        let a = BitSet::<2>::from_words([0b1100, 1]);
+        let b = BitSet::<2>::from_words([0b1010, 3]);
+        assert_eq!(a.union(&b).as_words(), &[0b1110, 3]);
+        assert_eq!(a.intersection(&b).as_words(), &[0b1000, 1]);
+        assert_eq!(a.difference(&b).as_words(), &[0b0100, 0]);
+        assert_eq!(alloc::format!("{a:?}"), "{2, 3, 64}");
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_bitset_out_of_bounds_panics() {
+        BitSet::<1>::new().set(64);
+    }
+
+    #[test]
+    fn test_bitvec_push_get_resize() {
+        let mut bits: BitVec = [true, false, true].into_iter().collect();
+        assert_eq!(bits.len(), 3);
+        assert_eq!(bits.get(2), Some(true));
+        assert_eq!(bits.get(3), None);
+
+        bits.resize(130, true);
+        assert_eq!(bits.len(), 130);
+        assert_eq!(bits.count_ones(), 129);
+        assert_eq!(bits.as_words().len(), 3);
+        assert_eq!(bits.as_words()[2], 0b11);
+
+        bits.resize(65, false);
+        assert_eq!(bits.count_ones(), 64);
+        assert_eq!(bits.last_one(), Some(64));
+        bits.resize(70, false);
+        assert_eq!(bits.count_ones(), 64);
+
+        bits.push(true);
+        assert_eq!(bits.last_one(), Some(70));
+        assert_eq!(bits.first_one(), Some(0));
+        assert_eq!(
+            alloc::format!("{:?}", BitVec::from_iter([false, true])),
+            "01"
+        );
+    }
+
+    #[test]
+    fn test_bitvec_from_words() {
+        let bits = BitVec::from_words(&[u64::MAX, u64::MAX, 7], 70);
+        assert_eq!(bits.len(), 70);
+        assert_eq!(bits.as_words(), &[u64::MAX, 0x3F]);
+        assert_eq!(bits.count_ones(), 70);
+        assert!(BitVec::from_words(&[], 0).is_empty());
+    }
+
+    #[test]
+    fn test_bitvec_set_operations_different_lengths() {
+        let mut short = BitVec::with_len(10);
+        short.set(1);
+        short.set(9);
+        let mut long = BitVec::with_len(200);
+        long.set(9);
+        long.set(199);
+
+        let mut union = short.clone();
+        union.union_with(&long);
+        assert_eq!(union.len(), 200);
+        assert_eq!(union.iter_ones().collect::<Vec<_>>(), [1, 9, 199]);
+
+        let mut intersection = long.clone();
+        intersection.intersect_with(&short);
+        assert_eq!(intersection.len(), 200);
+        assert_eq!(intersection.iter_ones().collect::<Vec<_>>(), [9]);
+
+        let mut difference = long.clone();
+        difference.difference_with(&short);
+        assert_eq!(difference.iter_ones().collect::<Vec<_>>(), [199]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_bitvec_out_of_bounds_panics() {
+        BitVec::with_len(10).test(10);
+    }
+
+    #[test]
+    fn test_random_against_btreeset() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let mut set = BitSet::<5>::new();
+        let mut bits = BitVec::with_len(BitSet::<5>::CAPACITY);
+        let mut expected = BTreeSet::new();
+
+        for _ in 0..2000 {
+            let index = (next_random(&mut state) % 320) as usize;
+            match next_random(&mut state) % 3 {
+                0 => {
+                    set.set(index);
+                    bits.set(index);
+                    expected.insert(index);
+                }
+                1 => {
+                    set.clear(index);
+                    bits.clear(index);
+                    expected.remove(&index);
+                }
+                _ => {
+                    set.toggle(index);
+                    bits.toggle(index);
+                    if !expected.remove(&index) {
+                        expected.insert(index);
+                    }
+                }
+            }
+            assert_eq!(set.test(index), expected.contains(&index));
+            assert_eq!(bits.test(index), expected.contains(&index));
+        }
+
+        assert!(set.iter_ones().eq(expected.iter().copied()));
+        assert!(bits.iter_ones().eq(expected.iter().copied()));
+        assert_eq!(set.first_one(), expected.first().copied());
+        assert_eq!(bits.last_one(), expected.last().copied());
+        assert_eq!(set.as_words().as_slice(), bits.as_words());
+    }
+}
@@ -0,0 +1,638 @@
+use crate::count_bits::BitsNeeded;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// The order in which bits are packed into each byte.
+///
+/// This trait is sealed: it is implemented by [`Msb`] and [`Lsb`], and can't be implemented
+/// outside this crate.
+pub trait BitOrder: private::Sealed {
+    #[doc(hidden)]
+    fn insert(acc: &mut u64, filled: &mut u32, value: u64, count: u32) -> Option<u64>;
+    #[doc(hidden)]
+    fn to_bytes(word: u64) -> [u8; 8];
+    #[doc(hidden)]
+    fn extract(window: [u8; 16], bit_offset: u32, count: u32) -> u64;
+    #[doc(hidden)]
+    fn zeros_before_one(value: u64, count: u32) -> u32;
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::Msb {}
+    impl Sealed for super::Lsb {}
+}
+
+/// Most significant bit first: the first bit written is the top bit of the first byte.
+///
+/// This is the order used by most file formats and network protocols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Msb;
+
+/// Least significant bit first: the first bit written is the bottom bit of the first byte.
+///
+/// This is the order used by e.g. DEFLATE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Lsb;
+
+impl BitOrder for Msb {
+    #[inline(always)]
+    fn insert(acc: &mut u64, filled: &mut u32, value: u64, count: u32) -> Option<u64> {
+        // Bits fill the accumulator from the top down.
+        if *filled + count < 64 {
+            *acc |= value << (64 - *filled - count);
+            *filled += count;
+            return None;
+        }
+
+        let spill = *filled + count - 64;
+        let word = *acc | (value >> spill);
+        *acc = if spill == 0 { 0 } else { value << (64 - spill) };
+        *filled = spill;
+        Some(word)
+    }
+
+    #[inline(always)]
+    fn to_bytes(word: u64) -> [u8; 8] {
+        word.to_be_bytes()
+    }
+
+    #[inline(always)]
+    fn extract(window: [u8; 16], bit_offset: u32, count: u32) -> u64 {
+        (u128::from_be_bytes(window) >> (128 - bit_offset - count)) as u64 & mask(count)
+    }
+
+    #[inline(always)]
+    fn zeros_before_one(value: u64, count: u32) -> u32 {
+        // The first bit read is bit `count - 1` of `value`.
+        (value.leading_zeros() - (64 - count)).min(count)
+    }
+}
+
+impl BitOrder for Lsb {
+    #[inline(always)]
+    fn insert(acc: &mut u64, filled: &mut u32, value: u64, count: u32) -> Option<u64> {
+        // Bits fill the accumulator from the bottom up.
+        *acc |= value << *filled;
+        if *filled + count < 64 {
+            *filled += count;
+            return None;
+        }
+
+        let word = *acc;
+        *acc = if *filled == 0 {
+            0
+        } else {
+            value >> (64 - *filled)
+        };
+        *filled = *filled + count - 64;
+        Some(word)
+    }
+
+    #[inline(always)]
+    fn to_bytes(word: u64) -> [u8; 8] {
+        word.to_le_bytes()
+    }
+
+    #[inline(always)]
+    fn extract(window: [u8; 16], bit_offset: u32, count: u32) -> u64 {
+        (u128::from_le_bytes(window) >> bit_offset) as u64 & mask(count)
+    }
+
+    #[inline(always)]
+    fn zeros_before_one(value: u64, count: u32) -> u32 {
+        // The first bit read is bit 0 of `value`.
+        value.trailing_zeros().min(count)
+    }
+}
+
+/// A destination for the bytes produced by a [`BitWriter`].
+///
+/// Implemented for [`Vec<u8>`], which grows as needed, and for `&mut [u8]`, which fails
+/// with [`BufferFull`] once the slice is exhausted.
+pub trait BitSink {
+    /// The error returned when bytes can't be written.
+    type Error;
+
+    /// Writes all of `bytes` to the sink, or none of them.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl BitSink for Vec<u8> {
+    type Error = Infallible;
+
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Infallible> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl BitSink for &mut [u8] {
+    type Error = BufferFull;
+
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), BufferFull> {
+        if self.len() < bytes.len() {
+            return Err(BufferFull);
+        }
+
+        let (head, tail) = core::mem::take(self).split_at_mut(bytes.len());
+        head.copy_from_slice(bytes);
+        *self = tail;
+        Ok(())
+    }
+}
+
+/// The error returned when a [`BitWriter`] runs out of space in a `&mut [u8]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferFull;
+
+impl fmt::Display for BufferFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("bit writer buffer is full")
+    }
+}
+
+impl core::error::Error for BufferFull {}
+
+/// Writes values of arbitrary bit widths to a [`BitSink`].
+///
+/// Bits are gathered in a 64-bit accumulator and written to the sink a word at a time.
+/// Call [`BitWriter::finish`] to write the last partial word, padded with zero bits to a
+/// whole byte.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::bitstream::{BitReader, BitWriter, Msb};
+/// let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+/// writer.write_bits(0b101, 3).unwrap();
+/// writer.write_bits(0b11111, 5).unwrap();
+/// writer.write_bits(0x3FF, 10).unwrap();
+/// let bytes = writer.finish().unwrap();
+/// assert_eq!(bytes, [0b1011_1111, 0xFF, 0b1100_0000]);
+///
+/// let mut reader = BitReader::<Msb>::new(&bytes);
+/// assert_eq!(reader.read_bits(3), Some(0b101));
+/// assert_eq!(reader.read_bits(5), Some(0b11111));
+/// assert_eq!(reader.read_bits(10), Some(0x3FF));
+/// ```
+pub struct BitWriter<S: BitSink, O: BitOrder = Msb> {
+    sink: S,
+    acc: u64,
+    filled: u32,
+    words_written: usize,
+    order: PhantomData<O>,
+}
+
+impl<S: BitSink, O: BitOrder> BitWriter<S, O> {
+    /// Creates a writer that appends to `sink`.
+    #[inline]
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            acc: 0,
+            filled: 0,
+            words_written: 0,
+            order: PhantomData,
+        }
+    }
+
+    /// Returns the number of bits written so far.
+    #[inline]
+    pub fn bits_written(&self) -> usize {
+        self.words_written * 64 + self.filled as usize
+    }
+
+    /// Writes the low `count` bits of `value`. Any higher bits of `value` are ignored.
+    ///
+    /// If the sink returns an error, the writer is left as it was before the call, so the
+    /// write can be retried once the sink has room.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is greater than 64.
+    #[inline]
+    pub fn write_bits(&mut self, value: u64, count: u32) -> Result<(), S::Error> {
+        assert!(count <= 64, "cannot write more than 64 bits at once");
+        if count == 0 {
+            return Ok(());
+        }
+
+        let value = value & mask(count);
+        let (acc, filled) = (self.acc, self.filled);
+        match O::insert(&mut self.acc, &mut self.filled, value, count) {
+            Some(word) => self.flush_word(word).inspect_err(|_| {
+                // Put the completed word back, so no bits are lost.
+                self.acc = acc;
+                self.filled = filled;
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes every value in `values` at the width of the largest one, as given by
+    /// [`BitsNeeded`], returning that width.
+    ///
+    /// The width is not written to the stream; store it alongside so the column can be
+    /// read back with [`BitReader::read_column`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::bitstream::{BitReader, BitWriter, Lsb};
+    /// let ids: [u16; 4] = [3, 17, 9, 30];
+    /// let mut writer = BitWriter::<_, Lsb>::new(Vec::new());
+    /// let width = writer.write_column(&ids).unwrap();
+    /// let bytes = writer.finish().unwrap();
+    /// assert_eq!(width, 5);
+    /// assert_eq!(bytes.len(), 3);
+    ///
+    /// let mut values = [0u64; 4];
+    /// BitReader::<Lsb>::new(&bytes).read_column(width, &mut values).unwrap();
+    /// assert_eq!(values, [3, 17, 9, 30]);
+    /// ```
+    pub fn write_column<T>(&mut self, values: &[T]) -> Result<u32, S::Error>
+    where
+        T: BitsNeeded + Copy + Into<u64>,
+    {
+        let width = values
+            .iter()
+            .map(|value| value.bits_needed_to_store())
+            .max()
+            .unwrap_or(0);
+
+        for &value in values {
+            self.write_bits(value.into(), width)?;
+        }
+        Ok(width)
+    }
+
+    /// Writes any remaining bits, padded with zero bits to a whole byte, and returns the sink.
+    pub fn finish(mut self) -> Result<S, S::Error> {
+        let bytes = O::to_bytes(self.acc);
+        let len = self.filled.div_ceil(8) as usize;
+        self.sink.write_bytes(&bytes[..len])?;
+        Ok(self.sink)
+    }
+
+    #[inline(always)]
+    fn flush_word(&mut self, word: u64) -> Result<(), S::Error> {
+        self.sink.write_bytes(&O::to_bytes(word))?;
+        self.words_written += 1;
+        Ok(())
+    }
+}
+
+/// Reads values of arbitrary bit widths from a byte slice.
+///
+/// Each read loads a 128-bit window around the current position, so a value of up to 64
+/// bits is extracted with a single shift and mask, whatever its alignment.
+///
+/// See [`BitWriter`] for an example.
+#[derive(Debug, Clone)]
+pub struct BitReader<'a, O: BitOrder = Msb> {
+    bytes: &'a [u8],
+    position: usize,
+    order: PhantomData<O>,
+}
+
+impl<'a, O: BitOrder> BitReader<'a, O> {
+    /// Creates a reader over `bytes`, starting at the first bit.
+    #[inline]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            order: PhantomData,
+        }
+    }
+
+    /// Returns the number of bits read so far.
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of bits left to read, including any padding bits at the end.
+    #[inline]
+    pub const fn bits_remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.position
+    }
+
+    /// Reads the next `count` bits, or returns [`None`] if fewer than `count` bits remain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is greater than 64.
+    #[inline]
+    pub fn read_bits(&mut self, count: u32) -> Option<u64> {
+        assert!(count <= 64, "cannot read more than 64 bits at once");
+        if count as usize > self.bits_remaining() {
+            return None;
+        }
+        if count == 0 {
+            return Some(0);
+        }
+
+        let start = self.position / 8;
+        let window = match self.bytes.get(start..start + 16) {
+            Some(window) => window.try_into().unwrap(),
+            None => {
+                // Near the end of the buffer; pad the window with zeroes.
+                let mut window = [0u8; 16];
+                let tail = &self.bytes[start..];
+                window[..tail.len()].copy_from_slice(tail);
+                window
+            }
+        };
+
+        let value = O::extract(window, (self.position % 8) as u32, count);
+        self.position += count as usize;
+        Some(value)
+    }
+
+    /// Reads zero bits up to and including the next one bit, and returns the number of zero
+    /// bits, or [`None`] if the reader runs out of bits first, which leaves it at the end.
+    ///
+    /// This reads a unary number, such as the prefix of an Elias gamma code, up to 64 bits
+    /// at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::bitstream::{BitReader, Msb};
+    /// let mut reader = BitReader::<Msb>::new(&[0b0001_1000]);
+    /// assert_eq!(reader.read_unary(), Some(3));
+    /// assert_eq!(reader.read_unary(), Some(0));
+    /// assert_eq!(reader.read_unary(), None);
+    /// ```
+    pub fn read_unary(&mut self) -> Option<usize> {
+        let mut zeros = 0;
+        loop {
+            let count = self.bits_remaining().min(64) as u32;
+            if count == 0 {
+                return None;
+            }
+
+            let start = self.position;
+            let bits = self.read_bits(count)?;
+            let run = O::zeros_before_one(bits, count);
+            if run < count {
+                // Step back to just after the one bit.
+                self.position = start + run as usize + 1;
+                return Some(zeros + run as usize);
+            }
+            zeros += count as usize;
+        }
+    }
+
+    /// Reads `out.len()` values of `width` bits each, as written by
+    /// [`BitWriter::write_column`].
+    ///
+    /// Returns [`None`] if the reader runs out of bits; `out` may then be partially filled.
+    pub fn read_column(&mut self, width: u32, out: &mut [u64]) -> Option<()> {
+        for value in out {
+            *value = self.read_bits(width)?;
+        }
+        Some(())
+    }
+}
+
+/// Returns a mask of the low `count` bits, for `count` in `1..=64`.
+#[inline(always)]
+pub(crate) fn mask(count: u32) -> u64 {
+    u64::MAX >> (64 - count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::next_random;
+    use alloc::vec;
+    use rstest::rstest;
+
+    fn random_fields(count: usize) -> Vec<(u64, u32)> {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        (0..count)
+            .map(|_| {
+                let width = (next_random(&mut state) % 65) as u32;
+                let value = next_random(&mut state);
+                (value, width)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_msb_layout() {
+        let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+        writer.write_bits(1, 1).unwrap();
+        writer.write_bits(0, 2).unwrap();
+        writer.write_bits(0xABCD, 16).unwrap();
+        assert_eq!(writer.bits_written(), 19);
+        assert_eq!(
+            writer.finish().unwrap(),
+            [0b1001_0101, 0b0111_1001, 0b1010_0000]
+        );
+    }
+
+    #[test]
+    fn test_lsb_layout() {
+        let mut writer = BitWriter::<_, Lsb>::new(Vec::new());
+        writer.write_bits(1, 1).unwrap();
+        writer.write_bits(0, 2).unwrap();
+        writer.write_bits(0xABCD, 16).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            [0b0110_1001, 0b0101_1110, 0b0000_0101]
+        );
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(u64::MAX, 64)]
+    #[case(0xFF, 4)]
+    #[case(0x1_0000_0001, 33)]
+    fn test_write_masks_value(#[case] value: u64, #[case] count: u32) {
+        let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+        writer.write_bits(value, count).unwrap();
+        writer.write_bits(0, 64 - count).unwrap();
+        let bytes = writer.finish().unwrap();
+        let expected = if count == 0 { 0 } else { value << (64 - count) };
+        assert_eq!(bytes, expected.to_be_bytes(), "Failed for input: {value:?}");
+    }
+
+    #[test]
+    fn test_round_trip_msb() {
+        let fields = random_fields(2000);
+        let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+        for &(value, width) in &fields {
+            writer.write_bits(value, width).unwrap();
+        }
+        let total_bits = writer.bits_written();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes.len(), total_bits.div_ceil(8));
+
+        let mut reader = BitReader::<Msb>::new(&bytes);
+        for &(value, width) in &fields {
+            let expected = if width == 0 { 0 } else { value & mask(width) };
+            assert_eq!(
+                reader.read_bits(width),
+                Some(expected),
+                "Failed for width: {width}"
+            );
+        }
+        assert_eq!(reader.position(), total_bits);
+        assert!(reader.bits_remaining() < 8);
+    }
+
+    #[test]
+    fn test_round_trip_lsb() {
+        let fields = random_fields(2000);
+        let mut writer = BitWriter::<_, Lsb>::new(Vec::new());
+        for &(value, width) in &fields {
+            writer.write_bits(value, width).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = BitReader::<Lsb>::new(&bytes);
+        for &(value, width) in &fields {
+            let expected = if width == 0 { 0 } else { value & mask(width) };
+            assert_eq!(
+                reader.read_bits(width),
+                Some(expected),
+                "Failed for width: {width}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_slice_sink() {
+        let mut buffer = [0u8; 9];
+        let mut writer = BitWriter::<_, Msb>::new(&mut buffer[..]);
+        writer.write_bits(u64::MAX, 64).unwrap();
+        writer.write_bits(0b1, 1).unwrap();
+        let rest = writer.finish().unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            buffer,
+            [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x80]
+        );
+
+        let mut buffer = [0u8; 7];
+        let mut writer = BitWriter::<_, Msb>::new(&mut buffer[..]);
+        assert_eq!(writer.write_bits(u64::MAX, 64), Err(BufferFull));
+    }
+
+    /// A sink that accepts up to `limit` bytes.
+    struct LimitedSink {
+        bytes: Vec<u8>,
+        limit: usize,
+    }
+
+    impl BitSink for LimitedSink {
+        type Error = BufferFull;
+
+        fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), BufferFull> {
+            if self.bytes.len() + bytes.len() > self.limit {
+                return Err(BufferFull);
+            }
+            self.bytes.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    fn check_write_retry_after_error<O: BitOrder>() {
+        let sink = LimitedSink {
+            bytes: Vec::new(),
+            limit: 0,
+        };
+        let mut writer = BitWriter::<_, O>::new(sink);
+        writer.write_bits(0x0ABC_DEF0_1234_5678, 60).unwrap();
+        assert_eq!(writer.write_bits(0b1011_0110, 8), Err(BufferFull));
+        assert_eq!(writer.bits_written(), 60);
+
+        writer.sink.limit = 16;
+        writer.write_bits(0b1011_0110, 8).unwrap();
+        assert_eq!(writer.bits_written(), 68);
+        let bytes = writer.finish().unwrap().bytes;
+
+        let mut reader = BitReader::<O>::new(&bytes);
+        assert_eq!(reader.read_bits(60), Some(0x0ABC_DEF0_1234_5678));
+        assert_eq!(reader.read_bits(8), Some(0b1011_0110));
+        assert_eq!(reader.read_bits(4), Some(0));
+        assert_eq!(reader.bits_remaining(), 0);
+    }
+
+    #[test]
+    fn test_write_retry_after_error() {
+        check_write_retry_after_error::<Msb>();
+        check_write_retry_after_error::<Lsb>();
+    }
+
+    #[test]
+    fn test_read_past_end() {
+        let bytes = [0xAB];
+        let mut reader = BitReader::<Lsb>::new(&bytes);
+        assert_eq!(reader.read_bits(9), None);
+        assert_eq!(reader.read_bits(0), Some(0));
+        assert_eq!(reader.read_bits(8), Some(0xAB));
+        assert_eq!(reader.read_bits(1), None);
+        assert_eq!(reader.bits_remaining(), 0);
+    }
+
+    fn check_read_unary<O: BitOrder>() {
+        let runs = [0usize, 1, 7, 63, 64, 65, 130, 3];
+        let mut writer = BitWriter::<_, O>::new(Vec::new());
+        writer.write_bits(0b1, 3).unwrap();
+        for &run in &runs {
+            for _ in 0..run {
+                writer.write_bits(0, 1).unwrap();
+            }
+            writer.write_bits(1, 1).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = BitReader::<O>::new(&bytes);
+        assert_eq!(reader.read_bits(3), Some(0b1));
+        for &run in &runs {
+            assert_eq!(reader.read_unary(), Some(run), "Failed for run: {run}");
+        }
+        // Only padding is left.
+        assert_eq!(reader.read_unary(), None);
+    }
+
+    #[test]
+    fn test_read_unary() {
+        check_read_unary::<Msb>();
+        check_read_unary::<Lsb>();
+        assert_eq!(BitReader::<Msb>::new(&[]).read_unary(), None);
+    }
+
+    #[test]
+    fn test_column_round_trip() {
+        let values: Vec<u32> = (0..1000).map(|x| (x * 7919) % 1500).collect();
+        let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+        let width = writer.write_column(&values).unwrap();
+        assert_eq!(width, 11);
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes.len(), (1000 * 11usize).div_ceil(8));
+
+        let mut unpacked = vec![0u64; values.len()];
+        let mut reader = BitReader::<Msb>::new(&bytes);
+        assert_eq!(reader.read_column(width, &mut unpacked), Some(()));
+        assert!(values.iter().zip(&unpacked).all(|(&a, &b)| a as u64 == b));
+    }
+
+    #[test]
+    fn test_column_of_zeroes() {
+        let mut writer = BitWriter::<_, Lsb>::new(Vec::new());
+        assert_eq!(writer.write_column(&[0u8; 100]), Ok(0));
+        assert_eq!(writer.write_column::<u64>(&[]), Ok(0));
+        assert!(writer.finish().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,635 @@
+use crate::bitstream::{BitOrder, BitReader, BitSink, BitWriter};
+use crate::count_bits::BitsNeeded;
+use core::fmt;
+
+/// A variable-length code for unsigned integers, written to a [`BitWriter`] and read back
+/// from a [`BitReader`].
+///
+/// Every code here is a unary prefix (zero bits ended by a one bit) followed by a fixed
+/// number of payload bits, so streams read back the same with [`Msb`](crate::bitstream::Msb)
+/// and [`Lsb`](crate::bitstream::Lsb) bit orders. All of them map 0 to their shortest
+/// codeword.
+pub trait UniversalCode {
+    /// Returns the number of bits `value` takes once written.
+    fn encoded_bits(&self, value: u64) -> u64;
+
+    /// Writes `value` to `writer`.
+    fn write<S: BitSink, O: BitOrder>(
+        &self,
+        writer: &mut BitWriter<S, O>,
+        value: u64,
+    ) -> Result<(), S::Error>;
+
+    /// Reads a value from `reader`.
+    ///
+    /// On error, the reader may have advanced past part of the codeword.
+    fn read<O: BitOrder>(&self, reader: &mut BitReader<'_, O>) -> Result<u64, DecodeError>;
+}
+
+/// An error returned by [`UniversalCode::read`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of a codeword.
+    Truncated,
+    /// The codeword is well-formed, but its value does not fit in a `u64`.
+    Overflow,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecodeError::Truncated => "input ended inside a codeword",
+            DecodeError::Overflow => "codeword value does not fit in a u64",
+        })
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// The Elias gamma code.
+///
+/// As gamma cannot represent 0, `value + 1` is coded: `n` takes [`BitsNeeded`] `b` bits,
+/// and is written as `b - 1` zero bits followed by the `b` bits of `n`, whose top bit is one.
+/// A value takes `2 * b - 1` bits.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::bitstream::{BitReader, BitWriter, Msb};
+/// use nanokit::codes::{EliasGamma, UniversalCode};
+/// let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+/// EliasGamma.write(&mut writer, 0).unwrap();
+/// EliasGamma.write(&mut writer, 4).unwrap();
+/// let bytes = writer.finish().unwrap();
+/// assert_eq!(bytes, [0b1_00101_00]);
+///
+/// let mut reader = BitReader::<Msb>::new(&bytes);
+/// assert_eq!(EliasGamma.read(&mut reader), Ok(0));
+/// assert_eq!(EliasGamma.read(&mut reader), Ok(4));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EliasGamma;
+
+impl UniversalCode for EliasGamma {
+    #[inline]
+    fn encoded_bits(&self, value: u64) -> u64 {
+        ExpGolomb::new(0).encoded_bits(value)
+    }
+
+    #[inline]
+    fn write<S: BitSink, O: BitOrder>(
+        &self,
+        writer: &mut BitWriter<S, O>,
+        value: u64,
+    ) -> Result<(), S::Error> {
+        ExpGolomb::new(0).write(writer, value)
+    }
+
+    #[inline]
+    fn read<O: BitOrder>(&self, reader: &mut BitReader<'_, O>) -> Result<u64, DecodeError> {
+        ExpGolomb::new(0).read(reader)
+    }
+}
+
+/// The Elias delta code.
+///
+/// `n = value + 1` takes `b` bits; `b` is written with the gamma code, followed by the low
+/// `b - 1` bits of `n`. This is shorter than [`EliasGamma`] for large values.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::bitstream::{BitReader, BitWriter, Msb};
+/// use nanokit::codes::{EliasDelta, EliasGamma, UniversalCode};
+/// assert_eq!(EliasDelta.encoded_bits(1_000_000), 28);
+/// assert_eq!(EliasGamma.encoded_bits(1_000_000), 39);
+///
+/// let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+/// EliasDelta.write(&mut writer, 1_000_000).unwrap();
+/// let bytes = writer.finish().unwrap();
+/// assert_eq!(EliasDelta.read(&mut BitReader::<Msb>::new(&bytes)), Ok(1_000_000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EliasDelta;
+
+impl UniversalCode for EliasDelta {
+    #[inline]
+    fn encoded_bits(&self, value: u64) -> u64 {
+        let bits = (value as u128 + 1).bits_needed_to_store();
+        let length_bits = bits.bits_needed_to_store();
+        (2 * length_bits - 1 + bits - 1) as u64
+    }
+
+    fn write<S: BitSink, O: BitOrder>(
+        &self,
+        writer: &mut BitWriter<S, O>,
+        value: u64,
+    ) -> Result<(), S::Error> {
+        let n = value as u128 + 1;
+        let bits = n.bits_needed_to_store();
+        let length_bits = bits.bits_needed_to_store();
+
+        write_zeros(writer, length_bits as u64 - 1)?;
+        writer.write_bits(1, 1)?;
+        writer.write_bits(bits as u64, length_bits - 1)?;
+        // Bit `bits - 1` of `n` is the implicit leading one.
+        writer.write_bits(n as u64, bits - 1)
+    }
+
+    fn read<O: BitOrder>(&self, reader: &mut BitReader<'_, O>) -> Result<u64, DecodeError> {
+        let length_zeros = reader.read_unary().ok_or(DecodeError::Truncated)?;
+        // `value + 1` has at most 65 bits, whose length has at most 7 bits.
+        if length_zeros > 6 {
+            return Err(DecodeError::Overflow);
+        }
+        let low = read_payload(reader, length_zeros as u32)?;
+        let bits = (1 << length_zeros) | low as u32;
+        if bits > 65 {
+            return Err(DecodeError::Overflow);
+        }
+
+        let payload = read_payload(reader, bits - 1)?;
+        from_offset((1 << (bits - 1)) | payload as u128, 1)
+    }
+}
+
+/// The exponential Golomb code of order `k`.
+///
+/// `n = value + 2^k` takes `b` bits, and is written as `b - 1 - k` zero bits followed by the
+/// `b` bits of `n`. Order 0 is the same as [`EliasGamma`]; higher orders spend `k` more bits
+/// on small values to save bits on large ones.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::codes::{ExpGolomb, UniversalCode};
+/// let code = ExpGolomb::new(3);
+/// assert_eq!(code.encoded_bits(0), 4);
+/// assert_eq!(code.encoded_bits(7), 4);
+/// assert_eq!(code.encoded_bits(8), 6);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpGolomb {
+    k: u32,
+}
+
+impl ExpGolomb {
+    /// Creates the code of order `k`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is not less than 64.
+    #[inline]
+    pub const fn new(k: u32) -> Self {
+        assert!(k < 64, "order must be less than 64");
+        Self { k }
+    }
+
+    /// Returns the order of the code.
+    #[inline]
+    pub const fn k(&self) -> u32 {
+        self.k
+    }
+}
+
+impl UniversalCode for ExpGolomb {
+    #[inline]
+    fn encoded_bits(&self, value: u64) -> u64 {
+        let bits = (value as u128 + (1 << self.k)).bits_needed_to_store();
+        (2 * bits - 1 - self.k) as u64
+    }
+
+    #[inline]
+    fn write<S: BitSink, O: BitOrder>(
+        &self,
+        writer: &mut BitWriter<S, O>,
+        value: u64,
+    ) -> Result<(), S::Error> {
+        let n = value as u128 + (1 << self.k);
+        let bits = n.bits_needed_to_store();
+
+        write_zeros(writer, (bits - 1 - self.k) as u64)?;
+        writer.write_bits(1, 1)?;
+        // Bit `bits - 1` of `n` is the implicit leading one.
+        writer.write_bits(n as u64, bits - 1)
+    }
+
+    #[inline]
+    fn read<O: BitOrder>(&self, reader: &mut BitReader<'_, O>) -> Result<u64, DecodeError> {
+        let zeros = reader.read_unary().ok_or(DecodeError::Truncated)?;
+        let payload_bits = zeros.saturating_add(self.k as usize);
+        if payload_bits > 64 {
+            return Err(DecodeError::Overflow);
+        }
+
+        let payload = read_payload(reader, payload_bits as u32)?;
+        from_offset((1 << payload_bits) | payload as u128, 1 << self.k)
+    }
+}
+
+/// The Golomb-Rice code with parameter `k`, i.e. the Golomb code with divisor `2^k`.
+///
+/// `value >> k` is written in unary as that many zero bits and a one bit, followed by the low
+/// `k` bits of `value`. This suits values near `2^k`, such as geometrically distributed
+/// residuals; use [`GolombRice::from_sample`] to pick `k`. The unary part grows linearly, so
+/// a value far above `2^k` takes a very long codeword.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::bitstream::{BitReader, BitWriter, Msb};
+/// use nanokit::codes::{GolombRice, UniversalCode};
+/// let residuals = [3u64, 9, 0, 14, 5, 7];
+/// let code = GolombRice::from_sample(&residuals);
+/// assert_eq!(code.k(), 2);
+///
+/// let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+/// for &value in &residuals {
+///     code.write(&mut writer, value).unwrap();
+/// }
+/// let bytes = writer.finish().unwrap();
+/// assert_eq!(bytes.len(), 4);
+///
+/// let mut reader = BitReader::<Msb>::new(&bytes);
+/// for &value in &residuals {
+///     assert_eq!(code.read(&mut reader), Ok(value));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GolombRice {
+    k: u32,
+}
+
+impl GolombRice {
+    /// Creates the code with parameter `k`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is not less than 64.
+    #[inline]
+    pub const fn new(k: u32) -> Self {
+        assert!(k < 64, "parameter must be less than 64");
+        Self { k }
+    }
+
+    /// Returns the parameter of the code.
+    #[inline]
+    pub const fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Returns the code whose parameter best fits values with the given mean.
+    ///
+    /// The expected length of a codeword is about `k + 1 + mean / 2^k` bits, which is
+    /// smallest near `2^k = mean * ln(2)`; this picks the `k` that minimizes it.
+    pub fn from_mean(mean: u64) -> Self {
+        Self::new(optimal_rice_k(mean as u128, 1))
+    }
+
+    /// Returns the code whose parameter best fits the mean of `values`, or `k = 0` for an
+    /// empty slice.
+    ///
+    /// See [`GolombRice::from_mean`]; the mean is not rounded, as the estimate is scaled by
+    /// the number of values instead.
+    pub fn from_sample(values: &[u64]) -> Self {
+        let sum: u128 = values.iter().map(|&value| value as u128).sum();
+        Self::new(optimal_rice_k(sum, values.len() as u128))
+    }
+}
+
+impl UniversalCode for GolombRice {
+    /// Returns the number of bits `value` takes once written, saturating at `u64::MAX`.
+    #[inline]
+    fn encoded_bits(&self, value: u64) -> u64 {
+        (value >> self.k).saturating_add(self.k as u64 + 1)
+    }
+
+    #[inline]
+    fn write<S: BitSink, O: BitOrder>(
+        &self,
+        writer: &mut BitWriter<S, O>,
+        value: u64,
+    ) -> Result<(), S::Error> {
+        write_zeros(writer, value >> self.k)?;
+        writer.write_bits(1, 1)?;
+        writer.write_bits(value, self.k)
+    }
+
+    #[inline]
+    fn read<O: BitOrder>(&self, reader: &mut BitReader<'_, O>) -> Result<u64, DecodeError> {
+        let quotient = reader.read_unary().ok_or(DecodeError::Truncated)?;
+        if quotient as u128 >= 1 << (64 - self.k) {
+            return Err(DecodeError::Overflow);
+        }
+
+        let remainder = read_payload(reader, self.k)?;
+        Ok(((quotient as u64) << self.k) | remainder)
+    }
+}
+
+/// Returns the `k` that minimizes the estimated total length, `count * (k + 1) + sum / 2^k`,
+/// of `count` Rice codewords whose values add up to `sum`.
+fn optimal_rice_k(sum: u128, count: u128) -> u32 {
+    (0..64)
+        .min_by_key(|&k| count * (k as u128 + 1) + (sum >> k))
+        .unwrap()
+}
+
+/// Writes `count` zero bits.
+#[inline]
+fn write_zeros<S: BitSink, O: BitOrder>(
+    writer: &mut BitWriter<S, O>,
+    mut count: u64,
+) -> Result<(), S::Error> {
+    while count > 0 {
+        let bits = count.min(64) as u32;
+        writer.write_bits(0, bits)?;
+        count -= bits as u64;
+    }
+    Ok(())
+}
+
+#[inline(always)]
+fn read_payload<O: BitOrder>(reader: &mut BitReader<'_, O>, bits: u32) -> Result<u64, DecodeError> {
+    reader.read_bits(bits).ok_or(DecodeError::Truncated)
+}
+
+/// Returns `n - offset`, if it fits in a `u64`.
+#[inline(always)]
+fn from_offset(n: u128, offset: u128) -> Result<u64, DecodeError> {
+    u64::try_from(n - offset).map_err(|_| DecodeError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitstream::{Lsb, Msb};
+    use crate::test_util::next_random;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use rstest::rstest;
+
+    /// Returns 0 to 3, every power of two up to `2^32` and its neighbours, and the top of the
+    /// `u32` range.
+    fn u32_edges() -> Vec<u64> {
+        let mut values = alloc::vec![0, 1, 2, 3, u32::MAX as u64 - 1, u32::MAX as u64];
+        for k in 2..=32 {
+            values.extend([(1u64 << k) - 1, 1 << k, (1 << k) + 1]);
+        }
+        values
+    }
+
+    /// Returns the `u32` edges, plus the same around every larger power of two and the top of
+    /// the `u64` range.
+    fn u64_edges() -> Vec<u64> {
+        let mut values = u32_edges();
+        for k in 33..64 {
+            values.extend([(1u64 << k) - 1, 1 << k, (1 << k) + 1]);
+        }
+        values.extend([u64::MAX - 1, u64::MAX]);
+        values
+    }
+
+    /// Writes `values` in one stream, checks its length, and reads them back.
+    fn check_round_trip<C: UniversalCode, O: BitOrder>(code: &C, values: &[u64]) {
+        let mut writer = BitWriter::<_, O>::new(Vec::new());
+        for &value in values {
+            code.write(&mut writer, value).unwrap();
+        }
+        let expected_bits: u64 = values.iter().map(|&value| code.encoded_bits(value)).sum();
+        assert_eq!(writer.bits_written() as u64, expected_bits);
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = BitReader::<O>::new(&bytes);
+        for &value in values {
+            assert_eq!(
+                code.read(&mut reader),
+                Ok(value),
+                "Failed for input: {value}"
+            );
+        }
+        assert_eq!(reader.position() as u64, expected_bits);
+        assert!(reader.bits_remaining() < 8);
+    }
+
+    fn check_round_trip_both_orders<C: UniversalCode>(code: &C, values: &[u64]) {
+        check_round_trip::<C, Msb>(code, values);
+        check_round_trip::<C, Lsb>(code, values);
+    }
+
+    /// Returns the bits of a single codeword, first bit first.
+    fn codeword<C: UniversalCode>(code: &C, value: u64) -> String {
+        let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+        code.write(&mut writer, value).unwrap();
+        let len = writer.bits_written();
+        let bytes = writer.finish().unwrap();
+        (0..len)
+            .map(|x| {
+                if bytes[x / 8] >> (7 - x % 8) & 1 == 1 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case(0, "1")]
+    #[case(1, "010")]
+    #[case(2, "011")]
+    #[case(4, "00101")]
+    #[case(14, "0001111")]
+    fn test_gamma_codewords(#[case] input: u64, #[case] expected: &str) {
+        assert_eq!(
+            codeword(&EliasGamma, input),
+            expected,
+            "Failed for input: {input}"
+        );
+    }
+
+    #[rstest]
+    #[case(0, "1")]
+    #[case(1, "0100")]
+    #[case(3, "01100")]
+    #[case(16, "001010001")]
+    fn test_delta_codewords(#[case] input: u64, #[case] expected: &str) {
+        assert_eq!(
+            codeword(&EliasDelta, input),
+            expected,
+            "Failed for input: {input}"
+        );
+    }
+
+    #[rstest]
+    #[case(0, 0, "1")]
+    #[case(0, 3, "00100")]
+    #[case(2, 0, "100")]
+    #[case(2, 3, "111")]
+    #[case(2, 4, "01000")]
+    fn test_exp_golomb_codewords(#[case] k: u32, #[case] input: u64, #[case] expected: &str) {
+        assert_eq!(
+            codeword(&ExpGolomb::new(k), input),
+            expected,
+            "Failed for input: {input} (k = {k})"
+        );
+    }
+
+    #[rstest]
+    #[case(0, 0, "1")]
+    #[case(0, 3, "0001")]
+    #[case(2, 9, "00101")]
+    #[case(3, 7, "1111")]
+    fn test_golomb_rice_codewords(#[case] k: u32, #[case] input: u64, #[case] expected: &str) {
+        assert_eq!(
+            codeword(&GolombRice::new(k), input),
+            expected,
+            "Failed for input: {input} (k = {k})"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_edges() {
+        let values = u64_edges();
+        check_round_trip_both_orders(&EliasGamma, &values);
+        check_round_trip_both_orders(&EliasDelta, &values);
+        for k in [0, 1, 5, 31, 32, 63] {
+            check_round_trip_both_orders(&ExpGolomb::new(k), &values);
+        }
+
+        // Keep the unary part of Rice codes short.
+        for k in [0, 1, 5, 16, 31, 32, 63] {
+            let values: Vec<u64> = values
+                .iter()
+                .copied()
+                .filter(|&value| value >> k < 1 << 12)
+                .collect();
+            assert!(!values.is_empty());
+            check_round_trip_both_orders(&GolombRice::new(k), &values);
+        }
+    }
+
+    #[test]
+    fn test_gamma_length_matches_bits_needed() {
+        for value in u32_edges() {
+            let bits = (value + 1).bits_needed_to_store() as u64;
+            assert_eq!(EliasGamma.encoded_bits(value), 2 * bits - 1);
+            assert_eq!(ExpGolomb::new(0).encoded_bits(value), 2 * bits - 1);
+        }
+        assert_eq!(EliasGamma.encoded_bits(u64::MAX), 129);
+        assert_eq!(EliasDelta.encoded_bits(u64::MAX), 77);
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        let values: Vec<u64> = (0..2000)
+            .map(|_| {
+                let shift = next_random(&mut state) % 64;
+                next_random(&mut state) >> shift
+            })
+            .collect();
+        check_round_trip_both_orders(&EliasGamma, &values);
+        check_round_trip_both_orders(&EliasDelta, &values);
+        check_round_trip_both_orders(&ExpGolomb::new(7), &values);
+
+        let small: Vec<u64> = values.iter().map(|value| value % 5000).collect();
+        check_round_trip_both_orders(&GolombRice::from_sample(&small), &small);
+    }
+
+    #[rstest]
+    #[case(&[], 0)]
+    #[case(&[0, 0, 0], 0)]
+    #[case(&[1], 0)]
+    #[case(&[100], 6)]
+    #[case(&[3, 9, 0, 14, 5, 7], 2)]
+    #[case(&[u64::MAX, u64::MAX], 63)]
+    fn test_golomb_rice_from_sample(#[case] values: &[u64], #[case] expected: u32) {
+        assert_eq!(
+            GolombRice::from_sample(values).k(),
+            expected,
+            "Failed for input: {values:?}"
+        );
+        if let [value] = values {
+            assert_eq!(GolombRice::from_mean(*value).k(), expected);
+        }
+    }
+
+    #[test]
+    fn test_golomb_rice_from_sample_is_near_optimal() {
+        // Roughly geometric values (trailing zeros of random words are geometric), with
+        // uniform noise on top.
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let values: Vec<u64> = (0..10_000)
+            .map(|_| {
+                let geometric = next_random(&mut state).trailing_zeros() as u64 * 24;
+                geometric + next_random(&mut state) % 24
+            })
+            .collect();
+
+        let total = |k: u32| -> u64 {
+            let code = GolombRice::new(k);
+            values.iter().map(|&value| code.encoded_bits(value)).sum()
+        };
+        let best = (0..20).map(total).min().unwrap();
+        let chosen = total(GolombRice::from_sample(&values).k());
+        assert!(
+            chosen * 100 <= best * 102,
+            "Chosen {chosen} bits vs best {best} bits"
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let empty = BitReader::<Msb>::new(&[]);
+        assert_eq!(
+            EliasGamma.read(&mut empty.clone()),
+            Err(DecodeError::Truncated)
+        );
+        assert_eq!(
+            EliasDelta.read(&mut empty.clone()),
+            Err(DecodeError::Truncated)
+        );
+        assert_eq!(
+            GolombRice::new(3).read(&mut empty.clone()),
+            Err(DecodeError::Truncated)
+        );
+
+        // A prefix promising 15 payload bits, with none following.
+        let bytes = [0x00, 0x01];
+        let mut reader = BitReader::<Msb>::new(&bytes);
+        assert_eq!(EliasGamma.read(&mut reader), Err(DecodeError::Truncated));
+
+        // 72 zero bits: a value of at least 2^72.
+        let mut bytes = [0u8; 10];
+        bytes[9] = 0x80;
+        let mut reader = BitReader::<Msb>::new(&bytes);
+        assert_eq!(EliasGamma.read(&mut reader), Err(DecodeError::Overflow));
+
+        // A gamma codeword for `2^65 - 1`, past `u64::MAX + 1`.
+        let mut writer = BitWriter::<_, Msb>::new(Vec::new());
+        write_zeros(&mut writer, 64).unwrap();
+        writer.write_bits(1, 1).unwrap();
+        writer.write_bits(u64::MAX, 64).unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(
+            EliasGamma.read(&mut BitReader::<Msb>::new(&bytes)),
+            Err(DecodeError::Overflow)
+        );
+        assert_eq!(
+            ExpGolomb::new(1).read(&mut BitReader::<Msb>::new(&bytes)),
+            Err(DecodeError::Overflow)
+        );
+        assert_eq!(
+            GolombRice::new(63).read(&mut BitReader::<Msb>::new(&[0b0011_0000])),
+            Err(DecodeError::Overflow)
+        );
+
+        // A delta length prefix of 127 bits.
+        let bytes = [0b0000_0011, 0xFF, 0xFF];
+        assert_eq!(
+            EliasDelta.read(&mut BitReader::<Msb>::new(&bytes)),
+            Err(DecodeError::Overflow)
+        );
+    }
+}
@@ -1,66 +1,991 @@
 // src/lib.rs
 
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+};
+
 /// A trait to calculate the minimum number of bits required to store a number.
 pub trait BitsNeeded {
     /// Returns the minimum number of bits required to store the number.
     ///
-    /// This returns the bits needed to store a given number.
+    /// This returns the bits needed to store a given number.
+    /// Examples:
+    ///
+    /// - 0: 0 bits
+    /// - 1: 1 bit (0b1)
+    /// - 2: 2 bits (0b10)
+    /// - 3: 2 bits (0b11)
+    ///  - ...
+    /// - 1023: 10 bits (0b1111111111)
+    /// - 1024: 11 bits (0b10000000000)
+    ///
+    fn bits_needed_to_store(&self) -> u32;
+}
+
+/// A trait to calculate the number of bits needed to store values in a range, relative to its
+/// minimum.
+///
+/// This is the width used by frame-of-reference encoding, where each value is stored as its
+/// offset from a shared base. Values in a narrow range far from zero, such as timestamps,
+/// then need far fewer bits than [`BitsNeeded`] on the maximum would give.
+pub trait RangeBitsNeeded: Sized {
+    /// Returns the number of bits needed to store any value in `min..=max` as an offset
+    /// from `min`.
+    ///
+    /// Examples:
+    ///
+    /// - 1000..=1000: 0 bits
+    /// - 1000..=1001: 1 bit
+    /// - 1000..=1255: 8 bits
+    /// - -128..=127: 8 bits
+    ///
+    /// The bounds may be given in either order: a `min` greater than `max` gives the same
+    /// width as the swapped range.
+    fn bits_needed_for_range(min: Self, max: Self) -> u32;
+
+    /// Returns the `(base, width)` pair for frame-of-reference encoding of `values`: the
+    /// minimum value, and the bits needed for each value's offset from it.
+    ///
+    /// An empty slice gives `(0, 0)`.
+    fn frame_of_reference(values: &[Self]) -> (Self, u32);
+}
+
+/// A companion trait to [`BitsNeeded`] for the widths usually derived from the bit count.
+pub trait StorageNeeded {
+    /// Returns the minimum number of bytes required to store the number.
+    ///
+    /// Examples:
+    ///
+    /// - 0: 0 bytes
+    /// - 255: 1 byte
+    /// - 256: 2 bytes
+    ///
+    fn bytes_needed_to_store(&self) -> u32;
+
+    /// Returns the minimum number of 4-bit nibbles (hex digits) required to store the number.
+    ///
+    /// Examples:
+    ///
+    /// - 0: 0 nibbles
+    /// - 15: 1 nibble (0xF)
+    /// - 16: 2 nibbles (0x10)
+    ///
+    fn nibbles_needed(&self) -> u32;
+
+    /// Returns the smallest unsigned integer type that can hold the number's bits.
+    ///
+    /// Examples:
+    ///
+    /// - 0: [`UnsignedType::U8`]
+    /// - 255: [`UnsignedType::U8`]
+    /// - 256: [`UnsignedType::U16`]
+    /// - -1i32: [`UnsignedType::U32`] (all 32 bits are set)
+    ///
+    fn smallest_unsigned_type(&self) -> UnsignedType;
+}
+
+/// An unsigned integer type, as returned by [`StorageNeeded::smallest_unsigned_type`].
+///
+/// Variants are ordered by width, so the widest of several values is their maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UnsignedType {
+    /// [`u8`]
+    U8,
+    /// [`u16`]
+    U16,
+    /// [`u32`]
+    U32,
+    /// [`u64`]
+    U64,
+    /// [`u128`]
+    U128,
+}
+
+impl UnsignedType {
+    /// Returns the smallest unsigned type that can hold `bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than 128.
+    #[inline]
+    pub const fn from_bits(bits: u32) -> Self {
+        match bits {
+            0..=8 => UnsignedType::U8,
+            9..=16 => UnsignedType::U16,
+            17..=32 => UnsignedType::U32,
+            33..=64 => UnsignedType::U64,
+            65..=128 => UnsignedType::U128,
+            _ => panic!("no unsigned type is wider than 128 bits"),
+        }
+    }
+
+    /// Returns the width of the type in bits.
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        match self {
+            UnsignedType::U8 => u8::BITS,
+            UnsignedType::U16 => u16::BITS,
+            UnsignedType::U32 => u32::BITS,
+            UnsignedType::U64 => u64::BITS,
+            UnsignedType::U128 => u128::BITS,
+        }
+    }
+
+    /// Returns the width of the type in bytes.
+    #[inline]
+    pub const fn bytes(self) -> u32 {
+        self.bits() / 8
+    }
+}
+
+/// Macro to define the `const fn` equivalents of [`BitsNeeded::bits_needed_to_store`].
+macro_rules! define_const_bits_needed {
+    ($($name:ident: $t:ty),* $(,)?) => {
+        $(
+            #[doc = concat!("Returns the minimum number of bits required to store a `", stringify!($t), "`.")]
+            ///
+            /// This is the `const` equivalent of [`BitsNeeded::bits_needed_to_store`], for use in
+            /// array lengths and const generics; the trait implementation calls it.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use nanokit::count_bits::", stringify!($name), ";")]
+            #[doc = concat!("const WIDTH: u32 = ", stringify!($name), "(100);")]
+            /// let flags = [false; WIDTH as usize];
+            /// assert_eq!(flags.len(), 7);
+            /// ```
+            #[inline]
+            pub const fn $name(value: $t) -> u32 {
+                <$t>::BITS - value.leading_zeros()
+            }
+        )*
+    }
+}
+
+define_const_bits_needed! {
+    bits_needed_u8: u8,
+    bits_needed_u16: u16,
+    bits_needed_u32: u32,
+    bits_needed_u64: u64,
+    bits_needed_u128: u128,
+    bits_needed_usize: usize,
+    bits_needed_i8: i8,
+    bits_needed_i16: i16,
+    bits_needed_i32: i32,
+    bits_needed_i64: i64,
+    bits_needed_i128: i128,
+    bits_needed_isize: isize,
+}
+
+/// Macro to implement the `BitsNeeded` trait for multiple numeric types.
+///
+/// # Parameters
+///
+/// * `$type`: The numeric type (e.g., `u8`, `u16`, `i32`, etc.).
+/// * `$bits`: The total number of bits for the type (e.g., `8` for `u8`, `16` for `u16`, etc.).
+#[macro_export]
+macro_rules! impl_bits_needed {
+    ($($t:ty => $bits:expr),* $(,)?) => {
+        $(
+            impl BitsNeeded for $t {
+
+                fn bits_needed_to_store(&self) -> u32 {
+                    $bits - self.leading_zeros()
+                }
+            }
+        )*
+    }
+}
+
+/// Macro to implement the `BitsNeeded` trait for the built-in integer types.
+///
+/// # Parameters
+///
+/// * `$type`: The numeric type (e.g., `u8`, `u16`, `i32`, etc.).
+/// * `$bits_needed`: The `const fn` that counts the bits of that type (e.g., [`bits_needed_u8`]
+///   for `u8`), so the trait and the `const fn` can't disagree.
+macro_rules! impl_const_bits_needed {
+    ($($t:ty => $bits_needed:path),* $(,)?) => {
+        $(
+            impl BitsNeeded for $t {
+
+                fn bits_needed_to_store(&self) -> u32 {
+                    $bits_needed(*self)
+                }
+            }
+        )*
+    }
+}
+
+// Implement `BitsNeeded` for all unsigned integer types
+impl_const_bits_needed! {
+    u8 => bits_needed_u8,
+    u16 => bits_needed_u16,
+    u32 => bits_needed_u32,
+    u64 => bits_needed_u64,
+    u128 => bits_needed_u128,
+    usize => bits_needed_usize,
+}
+
+// Implement `BitsNeeded` for all signed integer types
+impl_const_bits_needed! {
+    i8 => bits_needed_i8,
+    i16 => bits_needed_i16,
+    i32 => bits_needed_i32,
+    i64 => bits_needed_i64,
+    i128 => bits_needed_i128,
+    isize => bits_needed_isize,
+}
+
+/// Macro to implement the `StorageNeeded` trait for the built-in integer types.
+///
+/// # Parameters
+///
+/// * `$type`: The numeric type (e.g., `u8`, `u16`, `i32`, etc.).
+/// * `$bits_needed`: The `const fn` that counts the bits of that type.
+macro_rules! impl_storage_needed {
+    ($($t:ty => $bits_needed:path),* $(,)?) => {
+        $(
+            impl StorageNeeded for $t {
+
+                fn bytes_needed_to_store(&self) -> u32 {
+                    $bits_needed(*self).div_ceil(8)
+                }
+
+                fn nibbles_needed(&self) -> u32 {
+                    $bits_needed(*self).div_ceil(4)
+                }
+
+                fn smallest_unsigned_type(&self) -> UnsignedType {
+                    UnsignedType::from_bits($bits_needed(*self))
+                }
+            }
+        )*
+    }
+}
+
+impl_storage_needed! {
+    u8 => bits_needed_u8,
+    u16 => bits_needed_u16,
+    u32 => bits_needed_u32,
+    u64 => bits_needed_u64,
+    u128 => bits_needed_u128,
+    usize => bits_needed_usize,
+    i8 => bits_needed_i8,
+    i16 => bits_needed_i16,
+    i32 => bits_needed_i32,
+    i64 => bits_needed_i64,
+    i128 => bits_needed_i128,
+    isize => bits_needed_isize,
+}
+
+/// Macro to implement the `RangeBitsNeeded` trait for the built-in integer types.
+///
+/// # Parameters
+///
+/// * `$type`: The numeric type (e.g., `u8`, `u16`, `i32`, etc.).
+/// * `$bits_needed`: The `const fn` that counts the bits of that type.
+macro_rules! impl_range_bits_needed {
+    ($($t:ty => $bits_needed:path),* $(,)?) => {
+        $(
+            impl RangeBitsNeeded for $t {
+
+                fn bits_needed_for_range(min: Self, max: Self) -> u32 {
+                    let (min, max) = if min <= max { (min, max) } else { (max, min) };
+                    // The wrapped difference has the same bits as the unsigned difference.
+                    $bits_needed(max.wrapping_sub(min))
+                }
+
+                fn frame_of_reference(values: &[Self]) -> (Self, u32) {
+                    let Some(&first) = values.first() else {
+                        return (0, 0);
+                    };
+
+                    let (min, max) = values
+                        .iter()
+                        .fold((first, first), |(min, max), &value| (min.min(value), max.max(value)));
+                    (min, Self::bits_needed_for_range(min, max))
+                }
+            }
+        )*
+    }
+}
+
+impl_range_bits_needed! {
+    u8 => bits_needed_u8,
+    u16 => bits_needed_u16,
+    u32 => bits_needed_u32,
+    u64 => bits_needed_u64,
+    u128 => bits_needed_u128,
+    usize => bits_needed_usize,
+    i8 => bits_needed_i8,
+    i16 => bits_needed_i16,
+    i32 => bits_needed_i32,
+    i64 => bits_needed_i64,
+    i128 => bits_needed_i128,
+    isize => bits_needed_isize,
+}
+
+/// A trait to calculate the number of bits required to store a [`NonZero`](core::num::NonZero)
+/// number.
+///
+/// As the number is never zero, it always needs at least one bit, so the result is a
+/// [`NonZeroU32`] as well.
+pub trait NonZeroBitsNeeded {
+    /// Returns the minimum number of bits required to store the number.
+    ///
+    /// This matches [`BitsNeeded::bits_needed_to_store`], as a [`NonZeroU32`].
+    fn bits_needed_nonzero(&self) -> NonZeroU32;
+}
+
+/// Macro to implement the `BitsNeeded` and `NonZeroBitsNeeded` traits for `NonZero` types.
+///
+/// # Parameters
+///
+/// * `$type`: The non-zero numeric type (e.g., `NonZeroU8`, `NonZeroI32`, etc.).
+/// * `$bits_needed`: The `const fn` that counts the bits of the underlying integer type.
+macro_rules! impl_nonzero_bits_needed {
+    ($($t:ty => $bits_needed:path),* $(,)?) => {
+        $(
+            impl BitsNeeded for $t {
+
+                fn bits_needed_to_store(&self) -> u32 {
+                    $bits_needed(self.get())
+                }
+            }
+
+            impl NonZeroBitsNeeded for $t {
+
+                fn bits_needed_nonzero(&self) -> NonZeroU32 {
+                    // SAFETY: A non-zero number has at least one set bit.
+                    unsafe { NonZeroU32::new_unchecked($bits_needed(self.get())) }
+                }
+            }
+        )*
+    }
+}
+
+// Implement `BitsNeeded` for all non-zero integer types
+impl_nonzero_bits_needed! {
+    NonZeroU8 => bits_needed_u8,
+    NonZeroU16 => bits_needed_u16,
+    NonZeroU32 => bits_needed_u32,
+    NonZeroU64 => bits_needed_u64,
+    NonZeroU128 => bits_needed_u128,
+    NonZeroUsize => bits_needed_usize,
+    NonZeroI8 => bits_needed_i8,
+    NonZeroI16 => bits_needed_i16,
+    NonZeroI32 => bits_needed_i32,
+    NonZeroI64 => bits_needed_i64,
+    NonZeroI128 => bits_needed_i128,
+    NonZeroIsize => bits_needed_isize,
+}
+
+impl<T: BitsNeeded> BitsNeeded for Wrapping<T> {
+    #[inline]
+    fn bits_needed_to_store(&self) -> u32 {
+        self.0.bits_needed_to_store()
+    }
+}
+
+impl<T: BitsNeeded> BitsNeeded for Saturating<T> {
+    #[inline]
+    fn bits_needed_to_store(&self) -> u32 {
+        self.0.bits_needed_to_store()
+    }
+}
+
+impl<T: BitsNeeded + ?Sized> BitsNeeded for &T {
+    #[inline]
+    fn bits_needed_to_store(&self) -> u32 {
+        (**self).bits_needed_to_store()
+    }
+}
+
+impl<T: BitsNeeded + ?Sized> BitsNeeded for &mut T {
+    #[inline]
+    fn bits_needed_to_store(&self) -> u32 {
+        (**self).bits_needed_to_store()
+    }
+}
+
+/// A trait to calculate the number of bits needed to store a signed number compactly.
+///
+/// [`BitsNeeded`] treats signed numbers as their raw bits, so any negative number needs the
+/// full width of the type. This trait instead measures the magnitude of the number.
+pub trait SignedBitsNeeded {
+    /// Returns the number of bits needed to store the number after ZigZag encoding.
+    ///
+    /// ZigZag maps `0, -1, 1, -2, 2, ...` to `0, 1, 2, 3, 4, ...`, so this is the
+    /// [`BitsNeeded`] of that unsigned value.
+    /// Examples:
+    ///
+    /// - 0: 0 bits
+    /// - -1: 1 bit (0b1)
+    /// - 1: 2 bits (0b10)
+    /// - -2: 2 bits (0b11)
+    /// - 63: 7 bits (0b1111110)
+    /// - -64: 7 bits (0b1111111)
+    ///
+    fn bits_needed_zigzag(&self) -> u32;
+
+    /// Returns the minimum width of a two's complement field that can store the number,
+    /// such that sign-extending the field gives the number back.
+    ///
+    /// Examples:
+    ///
+    /// - 0: 1 bit (0b0)
+    /// - -1: 1 bit (0b1)
+    /// - 1: 2 bits (0b01)
+    /// - -2: 2 bits (0b10)
+    /// - 127: 8 bits (0b01111111)
+    /// - -128: 8 bits (0b10000000)
+    ///
+    fn bits_needed_twos_complement_min(&self) -> u32;
+}
+
+/// Macro to implement the `SignedBitsNeeded` trait for multiple signed numeric types.
+///
+/// # Parameters
+///
+/// * `$type`: The signed numeric type (e.g., `i8`, `i32`, etc.).
+/// * `$unsigned`: The unsigned type of the same width (e.g., `u8` for `i8`).
+macro_rules! impl_signed_bits_needed {
+    ($($t:ty => $unsigned:ty),* $(,)?) => {
+        $(
+            impl SignedBitsNeeded for $t {
+
+                fn bits_needed_zigzag(&self) -> u32 {
+                    let zigzag = ((*self << 1) ^ (*self >> (<$t>::BITS - 1))) as $unsigned;
+                    <$t>::BITS - zigzag.leading_zeros()
+                }
+
+                fn bits_needed_twos_complement_min(&self) -> u32 {
+                    // Flipping negative numbers leaves the bits that differ from the sign bit.
+                    let magnitude = (*self ^ (*self >> (<$t>::BITS - 1))) as $unsigned;
+                    <$t>::BITS - magnitude.leading_zeros() + 1
+                }
+            }
+        )*
+    }
+}
+
+// Implement `SignedBitsNeeded` for all signed integer types
+impl_signed_bits_needed! {
+    i8 => u8,
+    i16 => u16,
+    i32 => u32,
+    i64 => u64,
+    i128 => u128,
+    isize => usize,
+}
+
+/// A trait to calculate bit widths across a whole slice of numbers at once.
+///
+/// These process the slice in fixed-size chunks with independent accumulators, which the
+/// compiler can auto-vectorize, rather than calling [`BitsNeeded::bits_needed_to_store`]
+/// on one element at a time. Use the [`max_bits_needed`] and [`bits_histogram`] functions
+/// to call them.
+pub trait SliceBitsNeeded: Sized {
+    /// The histogram type: `[usize; BITS + 1]`, with one entry per possible bit count.
+    type Histogram;
+
+    /// Returns the largest [`BitsNeeded::bits_needed_to_store`] of any value in `values`.
+    fn max_bits_needed(values: &[Self]) -> u32;
+
+    /// Returns how many values in `values` need each number of bits, from 0 to `BITS`.
+    fn bits_histogram(values: &[Self]) -> Self::Histogram;
+}
+
+/// Number of independent accumulators used by the [`SliceBitsNeeded`] implementations.
+const SLICE_LANES: usize = 8;
+
+/// Returns the largest number of bits needed to store any value in `values`,
+/// or 0 for an empty slice.
+///
+/// This ORs all values together and counts the bits of the result, which gives the same
+/// answer as taking the maximum of each value's [`BitsNeeded`].
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::count_bits::max_bits_needed;
+/// assert_eq!(max_bits_needed(&[3u32, 17, 9, 30]), 5);
+/// assert_eq!(max_bits_needed::<u64>(&[]), 0);
+/// ```
+#[inline]
+pub fn max_bits_needed<T: SliceBitsNeeded>(values: &[T]) -> u32 {
+    T::max_bits_needed(values)
+}
+
+/// Returns how many values in `values` need each number of bits, from 0 to the width of `T`.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::count_bits::bits_histogram;
+/// let histogram = bits_histogram(&[0u8, 1, 3, 2, 255]);
+/// assert_eq!(histogram, [1, 1, 2, 0, 0, 0, 0, 0, 1]);
+/// ```
+#[inline]
+pub fn bits_histogram<T: SliceBitsNeeded>(values: &[T]) -> T::Histogram {
+    T::bits_histogram(values)
+}
+
+/// Macro to implement the `SliceBitsNeeded` trait for multiple numeric types.
+///
+/// # Parameters
+///
+/// * `$type`: The numeric type (e.g., `u8`, `u16`, `i32`, etc.).
+/// * `$bits`: The total number of bits for the type (e.g., `8` for `u8`, `16` for `u16`, etc.).
+macro_rules! impl_slice_bits_needed {
+    ($($t:ty => $bits:expr),* $(,)?) => {
+        $(
+            impl SliceBitsNeeded for $t {
+                type Histogram = [usize; $bits as usize + 1];
+
+                fn max_bits_needed(values: &[Self]) -> u32 {
+                    // Independent lanes keep the loop free of a serial dependency.
+                    let mut lanes: [$t; SLICE_LANES] = [0; SLICE_LANES];
+                    let chunks = values.chunks_exact(SLICE_LANES);
+                    let remainder = chunks.remainder();
+                    for chunk in chunks {
+                        for (lane, &value) in lanes.iter_mut().zip(chunk) {
+                            *lane |= value;
+                        }
+                    }
+
+                    let combined = lanes.iter().chain(remainder).fold(0, |acc, &value| acc | value);
+                    $bits - combined.leading_zeros()
+                }
+
+                fn bits_histogram(values: &[Self]) -> Self::Histogram {
+                    // Alternate between 4 tables, so runs of values with the same width don't
+                    // wait on the previous increment of the same entry.
+                    let mut tables = [[0usize; $bits as usize + 1]; 4];
+                    let chunks = values.chunks_exact(4);
+                    let remainder = chunks.remainder();
+                    for chunk in chunks {
+                        tables[0][chunk[0].leading_zeros() as usize] += 1;
+                        tables[1][chunk[1].leading_zeros() as usize] += 1;
+                        tables[2][chunk[2].leading_zeros() as usize] += 1;
+                        tables[3][chunk[3].leading_zeros() as usize] += 1;
+                    }
+                    for &value in remainder {
+                        tables[0][value.leading_zeros() as usize] += 1;
+                    }
+
+                    // Tables are indexed by leading zeros; flip them into bits needed.
+                    let mut result = [0usize; $bits as usize + 1];
+                    for (zeros, total) in result.iter_mut().rev().enumerate() {
+                        *total = tables.iter().map(|table| table[zeros]).sum();
+                    }
+                    result
+                }
+            }
+        )*
+    }
+}
+
+// Implement `SliceBitsNeeded` for all integer types
+impl_slice_bits_needed! {
+    u8 => u8::BITS,
+    u16 => u16::BITS,
+    u32 => u32::BITS,
+    u64 => u64::BITS,
+    u128 => u128::BITS,
+    usize => usize::BITS,
+    i8 => i8::BITS,
+    i16 => i16::BITS,
+    i32 => i32::BITS,
+    i64 => i64::BITS,
+    i128 => i128::BITS,
+    isize => isize::BITS,
+}
+
+/// A trait to calculate the number of digits needed to format a number.
+///
+/// Zero is formatted as a single digit `0`. Negative numbers are counted as a `-` sign followed
+/// by the digits of their magnitude, so [`DigitsNeeded::decimal_digits`] matches the length of
+/// `value.to_string()`.
+///
+/// Note: `format!("{:x}", -1i8)` prints the two's complement bits (`ff`), not `-1`; use
+/// the unsigned type to size that output.
+pub trait DigitsNeeded {
+    /// Returns the number of characters needed to format the number in base 10.
+    ///
+    /// Examples:
+    ///
+    /// - 0: 1 digit
+    /// - 9: 1 digit
+    /// - 10: 2 digits
+    /// - -10: 3 characters (`-10`)
+    ///
+    fn decimal_digits(&self) -> u32;
+
+    /// Returns the number of characters needed to format the number in base 16.
+    ///
+    /// Examples:
+    ///
+    /// - 0: 1 digit
+    /// - 15: 1 digit (`f`)
+    /// - 16: 2 digits (`10`)
+    /// - -16: 3 characters (`-10`)
+    ///
+    fn hex_digits(&self) -> u32;
+
+    /// Returns the number of characters needed to format the number in base `radix`.
+    ///
+    /// Powers of two are derived from the bit count, and base 10 uses
+    /// [`DigitsNeeded::decimal_digits`]; other bases divide repeatedly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in `2..=36`.
+    fn digits_in_base(&self, radix: u32) -> u32;
+}
+
+/// `10^x` for every `x` whose power fits in a `u64`.
+const POW10_U64: [u64; 20] = {
+    let mut table = [1u64; 20];
+    let mut x = 1;
+    while x < table.len() {
+        table[x] = table[x - 1] * 10;
+        x += 1;
+    }
+    table
+};
+
+/// `10^x` for every `x` whose power fits in a `u128`.
+const POW10_U128: [u128; 39] = {
+    let mut table = [1u128; 39];
+    let mut x = 1;
+    while x < table.len() {
+        table[x] = table[x - 1] * 10;
+        x += 1;
+    }
+    table
+};
+
+/// Returns the number of decimal digits of a `value` that needs `bits` bits.
+///
+/// `bits * 1233 >> 12` approximates `bits * log10(2)`, which is either the digit count or one
+/// less; a single comparison against the table picks between the two.
+#[inline(always)]
+const fn decimal_digits_u64(value: u64, bits: u32) -> u32 {
+    let guess = (bits * 1233) >> 12;
+    let digits = guess + (value >= POW10_U64[guess as usize]) as u32;
+    if digits == 0 {
+        1
+    } else {
+        digits
+    }
+}
+
+/// Returns the number of decimal digits of a `value` that needs `bits` bits.
+///
+/// See [`decimal_digits_u64`].
+#[inline(always)]
+const fn decimal_digits_u128(value: u128, bits: u32) -> u32 {
+    let guess = (bits * 1233) >> 12;
+    let digits = guess + (value >= POW10_U128[guess as usize]) as u32;
+    if digits == 0 {
+        1
+    } else {
+        digits
+    }
+}
+
+/// Macro to implement the `DigitsNeeded` trait for multiple unsigned numeric types.
+///
+/// # Parameters
+///
+/// * `$type`: The unsigned numeric type (e.g., `u8`, `u32`, etc.).
+/// * `$decimal_digits`: The base 10 helper for the type, and the type it takes.
+macro_rules! impl_digits_needed {
+    ($($t:ty => $decimal_digits:path as $wide:ty),* $(,)?) => {
+        $(
+            impl DigitsNeeded for $t {
+
+                fn decimal_digits(&self) -> u32 {
+                    $decimal_digits(*self as $wide, self.bits_needed_to_store())
+                }
+
+                fn hex_digits(&self) -> u32 {
+                    self.bits_needed_to_store().div_ceil(4).max(1)
+                }
+
+                fn digits_in_base(&self, radix: u32) -> u32 {
+                    assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+                    if radix.is_power_of_two() {
+                        return self
+                            .bits_needed_to_store()
+                            .div_ceil(radix.trailing_zeros())
+                            .max(1);
+                    }
+                    if radix == 10 {
+                        return self.decimal_digits();
+                    }
+
+                    let radix = radix as $t;
+                    let mut value = *self;
+                    let mut digits = 1;
+                    while value >= radix {
+                        value /= radix;
+                        digits += 1;
+                    }
+                    digits
+                }
+            }
+        )*
+    }
+}
+
+// Implement `DigitsNeeded` for all unsigned integer types
+impl_digits_needed! {
+    u8 => decimal_digits_u64 as u64,
+    u16 => decimal_digits_u64 as u64,
+    u32 => decimal_digits_u64 as u64,
+    u64 => decimal_digits_u64 as u64,
+    u128 => decimal_digits_u128 as u128,
+    usize => decimal_digits_u64 as u64,
+}
+
+/// Macro to implement the `DigitsNeeded` trait for multiple signed numeric types.
+///
+/// # Parameters
+///
+/// * `$type`: The signed numeric type (e.g., `i8`, `i32`, etc.).
+/// * `$unsigned`: The unsigned type of the same width (e.g., `u8` for `i8`).
+macro_rules! impl_signed_digits_needed {
+    ($($t:ty => $unsigned:ty),* $(,)?) => {
+        $(
+            impl DigitsNeeded for $t {
+
+                fn decimal_digits(&self) -> u32 {
+                    self.unsigned_abs().decimal_digits() + self.is_negative() as u32
+                }
+
+                fn hex_digits(&self) -> u32 {
+                    self.unsigned_abs().hex_digits() + self.is_negative() as u32
+                }
+
+                fn digits_in_base(&self, radix: u32) -> u32 {
+                    <$unsigned>::digits_in_base(&self.unsigned_abs(), radix)
+                        + self.is_negative() as u32
+                }
+            }
+        )*
+    }
+}
+
+// Implement `DigitsNeeded` for all signed integer types
+impl_signed_digits_needed! {
+    i8 => u8,
+    i16 => u16,
+    i32 => u32,
+    i64 => u64,
+    i128 => u128,
+    isize => usize,
+}
+
+/// A trait for integer base 2 logarithms that are missing from the standard library.
+///
+/// Implemented for unsigned integer types.
+pub trait IntLog {
+    /// Returns the base 2 logarithm of the number, rounded up.
+    ///
+    /// This is the number of bits needed to index `self` items, so both 0 and 1 return 0.
+    ///
+    /// Examples:
+    ///
+    /// - 0: 0
+    /// - 1: 0
+    /// - 2: 1
+    /// - 3: 2
+    /// - 4: 2
+    /// - 5: 3
+    ///
+    fn ilog2_ceil(&self) -> u32;
+
+    /// Returns the base 2 logarithm of the number, rounded up, or [`None`] for 0.
+    fn checked_ilog2_ceil(&self) -> Option<u32>;
+}
+
+/// A trait for power-of-two rounding and alignment that never panics on overflow.
+///
+/// Implemented for unsigned integer types. Every `align` must be a power of two; the
+/// `checked_` methods return [`None`] when it is not, while the other methods only check it
+/// with a debug assertion and otherwise return an unspecified value.
+pub trait PowerOfTwo: Sized {
+    /// Returns the largest power of two less than or equal to the number, or 0 for 0.
+    ///
     /// Examples:
     ///
-    /// - 0: 0 bits
-    /// - 1: 1 bit (0b1)
-    /// - 2: 2 bits (0b10)
-    /// - 3: 2 bits (0b11)
-    ///  - ...
-    /// - 1023: 10 bits (0b1111111111)
-    /// - 1024: 11 bits (0b10000000000)
+    /// - 0: 0
+    /// - 1: 1
+    /// - 5: 4
+    /// - 8: 8
     ///
-    fn bits_needed_to_store(&self) -> u32;
+    fn prev_pow2(&self) -> Self;
+
+    /// Returns the largest power of two less than or equal to the number, or [`None`] for 0.
+    fn checked_prev_pow2(&self) -> Option<Self>;
+
+    /// Returns the smallest power of two greater than or equal to the number, or [`None`]
+    /// if it does not fit in the type.
+    ///
+    /// Examples:
+    ///
+    /// - 0: 1
+    /// - 5: 8
+    /// - 8: 8
+    /// - `u8::MAX`: [`None`]
+    ///
+    fn checked_next_pow2(&self) -> Option<Self>;
+
+    /// Returns the smallest power of two greater than or equal to the number, or 0 if it
+    /// does not fit in the type.
+    fn wrapping_next_pow2(&self) -> Self;
+
+    /// Returns `true` if the number is a multiple of `align`.
+    fn is_aligned(&self, align: Self) -> bool;
+
+    /// Rounds the number down to a multiple of `align`.
+    fn align_down(&self, align: Self) -> Self;
+
+    /// Rounds the number down to a multiple of `align`, or returns [`None`] if `align` is
+    /// not a power of two.
+    fn checked_align_down(&self, align: Self) -> Option<Self>;
+
+    /// Rounds the number up to a multiple of `align`, or returns [`None`] if the result does
+    /// not fit in the type or `align` is not a power of two.
+    ///
+    /// Examples:
+    ///
+    /// - 13, 8: Some(16)
+    /// - 16, 8: Some(16)
+    /// - 250u8, 8: [`None`]
+    ///
+    fn checked_align_up(&self, align: Self) -> Option<Self>;
+
+    /// Rounds the number up to a multiple of `align`, wrapping around to 0 if the result does
+    /// not fit in the type.
+    fn wrapping_align_up(&self, align: Self) -> Self;
 }
 
-/// Macro to implement the `BitsNeeded` trait for multiple numeric types.
+/// Macro to implement the `IntLog` and `PowerOfTwo` traits for multiple unsigned numeric types.
 ///
 /// # Parameters
 ///
-/// * `$type`: The numeric type (e.g., `u8`, `u16`, `i32`, etc.).
-/// * `$bits`: The total number of bits for the type (e.g., `8` for `u8`, `16` for `u16`, etc.).
-#[macro_export]
-macro_rules! impl_bits_needed {
-    ($($t:ty => $bits:expr),* $(,)?) => {
+/// * `$type`: The unsigned numeric type (e.g., `u8`, `u32`, etc.).
+macro_rules! impl_power_of_two {
+    ($($t:ty),* $(,)?) => {
         $(
-            impl BitsNeeded for $t {
+            impl IntLog for $t {
 
-                fn bits_needed_to_store(&self) -> u32 {
-                    $bits - self.leading_zeros()
+                fn ilog2_ceil(&self) -> u32 {
+                    if *self <= 1 {
+                        0
+                    } else {
+                        <$t>::BITS - (*self - 1).leading_zeros()
+                    }
+                }
+
+                fn checked_ilog2_ceil(&self) -> Option<u32> {
+                    if *self == 0 {
+                        None
+                    } else {
+                        Some(self.ilog2_ceil())
+                    }
+                }
+            }
+
+            impl PowerOfTwo for $t {
+
+                fn prev_pow2(&self) -> Self {
+                    self.checked_prev_pow2().unwrap_or(0)
+                }
+
+                fn checked_prev_pow2(&self) -> Option<Self> {
+                    let log = self.checked_ilog2()?;
+                    Some(1 << log)
+                }
+
+                fn checked_next_pow2(&self) -> Option<Self> {
+                    let log = self.ilog2_ceil();
+                    if log < <$t>::BITS {
+                        Some(1 << log)
+                    } else {
+                        None
+                    }
+                }
+
+                fn wrapping_next_pow2(&self) -> Self {
+                    self.checked_next_pow2().unwrap_or(0)
+                }
+
+                fn is_aligned(&self, align: Self) -> bool {
+                    debug_assert!(align.is_power_of_two(), "align must be a power of two");
+                    *self & align.wrapping_sub(1) == 0
+                }
+
+                fn align_down(&self, align: Self) -> Self {
+                    debug_assert!(align.is_power_of_two(), "align must be a power of two");
+                    *self & !align.wrapping_sub(1)
+                }
+
+                fn checked_align_down(&self, align: Self) -> Option<Self> {
+                    if align.is_power_of_two() {
+                        Some(self.align_down(align))
+                    } else {
+                        None
+                    }
+                }
+
+                fn checked_align_up(&self, align: Self) -> Option<Self> {
+                    if !align.is_power_of_two() {
+                        return None;
+                    }
+                    let mask = align - 1;
+                    self.checked_add(mask).map(|value| value & !mask)
+                }
+
+                fn wrapping_align_up(&self, align: Self) -> Self {
+                    debug_assert!(align.is_power_of_two(), "align must be a power of two");
+                    let mask = align.wrapping_sub(1);
+                    self.wrapping_add(mask) & !mask
                 }
             }
         )*
     }
 }
 
-// Implement `BitsNeeded` for all unsigned integer types
-impl_bits_needed! {
-    u8 => u8::BITS,
-    u16 => u16::BITS,
-    u32 => u32::BITS,
-    u64 => u64::BITS,
-    u128 => u128::BITS,
-    usize => usize::BITS,
-}
-
-// Implement `BitsNeeded` for all signed integer types
-impl_bits_needed! {
-    i8 => i8::BITS,
-    i16 => i16::BITS,
-    i32 => i32::BITS,
-    i64 => i64::BITS,
-    i128 => i128::BITS,
-    isize => isize::BITS,
+// Implement `IntLog` and `PowerOfTwo` for all unsigned integer types
+impl_power_of_two! {
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::BitsNeeded;
+    use super::{
+        bits_histogram, bits_needed_i128, bits_needed_i16, bits_needed_i32, bits_needed_i64,
+        bits_needed_i8, bits_needed_isize, bits_needed_u128, bits_needed_u16, bits_needed_u32,
+        bits_needed_u64, bits_needed_u8, bits_needed_usize, max_bits_needed, BitsNeeded,
+        DigitsNeeded, IntLog, NonZeroBitsNeeded, PowerOfTwo, RangeBitsNeeded, SignedBitsNeeded,
+        StorageNeeded, UnsignedType,
+    };
+    use core::num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+    };
     use rstest::rstest;
 
     #[rstest]
@@ -426,4 +1351,733 @@ mod tests {
             "Failed for input: {input} (isize)"
         );
     }
+
+    #[rstest]
+    #[case(0i32, 0, 1)]
+    #[case(-1i32, 1, 1)]
+    #[case(1i32, 2, 2)]
+    #[case(-2i32, 2, 2)]
+    #[case(63i32, 7, 7)]
+    #[case(-64i32, 7, 7)]
+    #[case(64i32, 8, 8)]
+    #[case(127i32, 8, 8)]
+    #[case(-128i32, 8, 8)]
+    #[case(i32::MAX, 32, 32)]
+    #[case(i32::MIN, 32, 32)]
+    fn test_signed_bits_needed_i32(
+        #[case] input: i32,
+        #[case] zigzag: u32,
+        #[case] twos_complement: u32,
+    ) {
+        assert_eq!(
+            input.bits_needed_zigzag(),
+            zigzag,
+            "Failed for input: {input} (i32)"
+        );
+        assert_eq!(
+            input.bits_needed_twos_complement_min(),
+            twos_complement,
+            "Failed for input: {input} (i32)"
+        );
+    }
+
+    #[test]
+    fn test_signed_bits_needed_extremes() {
+        assert_eq!(i64::MIN.bits_needed_zigzag(), 64);
+        assert_eq!(i64::MIN.bits_needed_twos_complement_min(), 64);
+        assert_eq!(i128::MAX.bits_needed_twos_complement_min(), 128);
+        assert_eq!((-1isize).bits_needed_zigzag(), 1);
+        assert_eq!(0isize.bits_needed_twos_complement_min(), 1);
+    }
+
+    /// Checks every `i8` and `i16` against a straightforward search for the narrowest width.
+    macro_rules! test_signed_bits_needed_exhaustive {
+        ($($name:ident: $t:ty),* $(,)?) => {
+            $(
+                #[test]
+                fn $name() {
+                    for input in <$t>::MIN..=<$t>::MAX {
+                        let value = input as i128;
+                        let zigzag = if value < 0 { -2 * value - 1 } else { 2 * value };
+                        let expected_zigzag = (0..=<$t>::BITS)
+                            .find(|&bits| zigzag < 1i128 << bits)
+                            .unwrap();
+                        let expected_twos = (1..=<$t>::BITS)
+                            .find(|&bits| {
+                                let half = 1i128 << (bits - 1);
+                                (-half..half).contains(&value)
+                            })
+                            .unwrap();
+
+                        assert_eq!(
+                            input.bits_needed_zigzag(),
+                            expected_zigzag,
+                            "Failed for input: {input}"
+                        );
+                        assert_eq!(
+                            input.bits_needed_twos_complement_min(),
+                            expected_twos,
+                            "Failed for input: {input}"
+                        );
+                    }
+                }
+            )*
+        };
+    }
+
+    test_signed_bits_needed_exhaustive! {
+        test_signed_bits_needed_all_i8: i8,
+        test_signed_bits_needed_all_i16: i16,
+    }
+
+    #[rstest]
+    #[case(1000u32, 1000u32, 0)]
+    #[case(1000u32, 1001u32, 1)]
+    #[case(1000u32, 1255u32, 8)]
+    #[case(1000u32, 1256u32, 9)]
+    #[case(0u32, u32::MAX, 32)]
+    #[case(u32::MAX, u32::MAX, 0)]
+    #[case(1255u32, 1000u32, 8)]
+    #[case(u32::MAX, 0u32, 32)]
+    fn test_bits_needed_for_range_u32(#[case] min: u32, #[case] max: u32, #[case] expected: u32) {
+        assert_eq!(
+            u32::bits_needed_for_range(min, max),
+            expected,
+            "Failed for input: {min}..={max} (u32)"
+        );
+    }
+
+    #[rstest]
+    #[case(-1i16, -1i16, 0)]
+    #[case(-1i16, 0i16, 1)]
+    #[case(-128i16, 127i16, 8)]
+    #[case(-5i16, 250i16, 8)]
+    #[case(i16::MIN, i16::MAX, 16)]
+    #[case(i16::MIN, -1i16, 15)]
+    fn test_bits_needed_for_range_i16(#[case] min: i16, #[case] max: i16, #[case] expected: u32) {
+        assert_eq!(
+            i16::bits_needed_for_range(min, max),
+            expected,
+            "Failed for input: {min}..={max} (i16)"
+        );
+    }
+
+    #[test]
+    fn test_bits_needed_for_range_all_i8() {
+        for min in i8::MIN..=i8::MAX {
+            for max in min..=i8::MAX {
+                let span = (max as i32 - min as i32) as u32;
+                assert_eq!(
+                    i8::bits_needed_for_range(min, max),
+                    span.bits_needed_to_store(),
+                    "Failed for input: {min}..={max} (i8)"
+                );
+                assert_eq!(
+                    i8::bits_needed_for_range(max, min),
+                    span.bits_needed_to_store(),
+                    "Failed for input: {max}..={min} (i8)"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_frame_of_reference() {
+        let timestamps = [
+            1_700_000_120u64,
+            1_700_000_000,
+            1_700_000_255,
+            1_700_000_001,
+        ];
+        assert_eq!(u64::frame_of_reference(&timestamps), (1_700_000_000, 8));
+        assert_eq!(i32::frame_of_reference(&[-3, 4, 0]), (-3, 3));
+        assert_eq!(u8::frame_of_reference(&[7]), (7, 0));
+        assert_eq!(u128::frame_of_reference(&[]), (0, 0));
+        assert_eq!(
+            isize::frame_of_reference(&[isize::MIN, isize::MAX]),
+            (isize::MIN, isize::BITS)
+        );
+        assert_eq!(usize::frame_of_reference(&[5, 3]), (3, 2));
+        assert_eq!(u16::frame_of_reference(&[0, u16::MAX]), (0, 16));
+        assert_eq!(i8::frame_of_reference(&[-1, -1]), (-1, 0));
+        assert_eq!(i64::frame_of_reference(&[10, 20]), (10, 4));
+        assert_eq!(i128::frame_of_reference(&[i128::MIN, 0]), (i128::MIN, 128));
+    }
+
+    /// Checks the slice helpers against per-element `bits_needed_to_store` at every length
+    /// up to a few chunks, so both the chunked loop and the remainder are covered.
+    macro_rules! test_slice_bits_needed {
+        ($($name:ident: $t:ty),* $(,)?) => {
+            $(
+                #[test]
+                fn $name() {
+                    let values: alloc::vec::Vec<$t> = (0..40u32)
+                        .map(|x| (0x9E37_79B9_7F4A_7C15u64.wrapping_mul(x as u64 + 1) >> (x * 3 % 64)) as $t)
+                        .collect();
+
+                    for len in 0..=values.len() {
+                        let values = &values[..len];
+                        let expected_max = values.iter().map(|v| v.bits_needed_to_store()).max().unwrap_or(0);
+                        assert_eq!(max_bits_needed(values), expected_max, "Failed for length: {len}");
+
+                        let mut expected_histogram = [0usize; <$t>::BITS as usize + 1];
+                        for value in values {
+                            expected_histogram[value.bits_needed_to_store() as usize] += 1;
+                        }
+                        assert_eq!(bits_histogram(values), expected_histogram, "Failed for length: {len}");
+                    }
+                }
+            )*
+        };
+    }
+
+    test_slice_bits_needed! {
+        test_slice_bits_needed_u8: u8,
+        test_slice_bits_needed_u16: u16,
+        test_slice_bits_needed_u32: u32,
+        test_slice_bits_needed_u64: u64,
+        test_slice_bits_needed_u128: u128,
+        test_slice_bits_needed_usize: usize,
+        test_slice_bits_needed_i8: i8,
+        test_slice_bits_needed_i16: i16,
+        test_slice_bits_needed_i32: i32,
+        test_slice_bits_needed_i64: i64,
+        test_slice_bits_needed_i128: i128,
+        test_slice_bits_needed_isize: isize,
+    }
+
+    #[test]
+    fn test_slice_bits_needed_negative() {
+        assert_eq!(max_bits_needed(&[1i32, -1, 2]), 32);
+        assert_eq!(bits_histogram(&[-1i8, 0, 64])[8], 1);
+    }
+
+    #[test]
+    fn test_const_bits_needed_in_const_contexts() {
+        const MAX_ID: u32 = 1000;
+        const WIDTH: u32 = bits_needed_u32(MAX_ID);
+        let slots = [0u8; bits_needed_u64(u64::MAX) as usize];
+        assert_eq!(WIDTH, 10);
+        assert_eq!(slots.len(), 64);
+
+        struct Field<const BITS: u32>;
+        impl<const BITS: u32> Field<BITS> {
+            const WIDTH: u32 = BITS;
+        }
+        assert_eq!(Field::<{ bits_needed_i8(-1) }>::WIDTH, 8);
+    }
+
+    /// Checks that the exported macro still accepts a type and its width in bits.
+    #[test]
+    fn test_impl_bits_needed_macro() {
+        struct Word(u32);
+        impl Word {
+            fn leading_zeros(&self) -> u32 {
+                self.0.leading_zeros()
+            }
+        }
+        crate::impl_bits_needed! { Word => 32 }
+
+        assert_eq!(Word(0).bits_needed_to_store(), 0);
+        assert_eq!(Word(1024).bits_needed_to_store(), 11);
+    }
+
+    /// Checks that the `const fn`s and the trait agree on every `u8`, `i8`, `u16` and `i16`,
+    /// and on powers of two for wider types.
+    #[test]
+    fn test_const_bits_needed_matches_trait() {
+        for value in 0..=u16::MAX {
+            assert_eq!(bits_needed_u16(value), value.bits_needed_to_store());
+            assert_eq!(
+                bits_needed_i16(value as i16),
+                (value as i16).bits_needed_to_store()
+            );
+        }
+        for value in 0..=u8::MAX {
+            assert_eq!(bits_needed_u8(value), value.bits_needed_to_store());
+            assert_eq!(
+                bits_needed_i8(value as i8),
+                (value as i8).bits_needed_to_store()
+            );
+        }
+        for shift in 0..128 {
+            let value = 1u128 << shift;
+            assert_eq!(bits_needed_u128(value), shift + 1);
+            assert_eq!(
+                bits_needed_i128(value as i128),
+                (value as i128).bits_needed_to_store()
+            );
+            assert_eq!(
+                bits_needed_u64(value as u64),
+                (value as u64).bits_needed_to_store()
+            );
+            assert_eq!(
+                bits_needed_i64(value as i64),
+                (value as i64).bits_needed_to_store()
+            );
+            assert_eq!(
+                bits_needed_u32(value as u32),
+                (value as u32).bits_needed_to_store()
+            );
+            assert_eq!(
+                bits_needed_i32(value as i32),
+                (value as i32).bits_needed_to_store()
+            );
+            assert_eq!(
+                bits_needed_usize(value as usize),
+                (value as usize).bits_needed_to_store()
+            );
+            assert_eq!(
+                bits_needed_isize(value as isize),
+                (value as isize).bits_needed_to_store()
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(0u64, 0, 0, UnsignedType::U8)]
+    #[case(1u64, 1, 1, UnsignedType::U8)]
+    #[case(15u64, 1, 1, UnsignedType::U8)]
+    #[case(16u64, 1, 2, UnsignedType::U8)]
+    #[case(255u64, 1, 2, UnsignedType::U8)]
+    #[case(256u64, 2, 3, UnsignedType::U16)]
+    #[case(65535u64, 2, 4, UnsignedType::U16)]
+    #[case(65536u64, 3, 5, UnsignedType::U32)]
+    #[case(u32::MAX as u64, 4, 8, UnsignedType::U32)]
+    #[case(1u64 << 32, 5, 9, UnsignedType::U64)]
+    #[case(u64::MAX, 8, 16, UnsignedType::U64)]
+    fn test_storage_needed_u64(
+        #[case] input: u64,
+        #[case] bytes: u32,
+        #[case] nibbles: u32,
+        #[case] smallest: UnsignedType,
+    ) {
+        assert_eq!(
+            input.bytes_needed_to_store(),
+            bytes,
+            "Failed for input: {input} (u64)"
+        );
+        assert_eq!(
+            input.nibbles_needed(),
+            nibbles,
+            "Failed for input: {input} (u64)"
+        );
+        assert_eq!(
+            input.smallest_unsigned_type(),
+            smallest,
+            "Failed for input: {input} (u64)"
+        );
+    }
+
+    #[test]
+    fn test_storage_needed_other_types() {
+        assert_eq!(u128::MAX.smallest_unsigned_type(), UnsignedType::U128);
+        assert_eq!(u128::MAX.bytes_needed_to_store(), 16);
+        assert_eq!((-1i8).bytes_needed_to_store(), 1);
+        assert_eq!((-1i32).smallest_unsigned_type(), UnsignedType::U32);
+        assert_eq!(127i16.smallest_unsigned_type(), UnsignedType::U8);
+        assert_eq!(0x1234usize.nibbles_needed(), 4);
+        assert_eq!(0xABu8.nibbles_needed(), 2);
+        assert_eq!(i64::MAX.bytes_needed_to_store(), 8);
+        assert_eq!(300u16.smallest_unsigned_type(), UnsignedType::U16);
+        assert_eq!(70000u32.bytes_needed_to_store(), 3);
+        assert_eq!(1isize.smallest_unsigned_type(), UnsignedType::U8);
+        assert_eq!((-1i128).nibbles_needed(), 32);
+    }
+
+    #[test]
+    fn test_storage_needed_all_u16() {
+        for value in 0..=u16::MAX {
+            let bits = value.bits_needed_to_store();
+            assert_eq!(value.bytes_needed_to_store(), bits.div_ceil(8));
+            assert_eq!(value.nibbles_needed(), bits.div_ceil(4));
+            let smallest = value.smallest_unsigned_type();
+            assert!(bits <= smallest.bits(), "Failed for input: {value} (u16)");
+            assert_eq!(smallest, UnsignedType::from_bits(bits));
+        }
+    }
+
+    #[rstest]
+    #[case(UnsignedType::U8, 8, 1)]
+    #[case(UnsignedType::U16, 16, 2)]
+    #[case(UnsignedType::U32, 32, 4)]
+    #[case(UnsignedType::U64, 64, 8)]
+    #[case(UnsignedType::U128, 128, 16)]
+    fn test_unsigned_type_widths(
+        #[case] input: UnsignedType,
+        #[case] bits: u32,
+        #[case] bytes: u32,
+    ) {
+        assert_eq!(input.bits(), bits, "Failed for input: {input:?}");
+        assert_eq!(input.bytes(), bytes, "Failed for input: {input:?}");
+        assert_eq!(UnsignedType::from_bits(bits), input);
+        assert!(UnsignedType::U8 <= input);
+    }
+
+    #[rstest]
+    #[case(NonZeroU8::MIN, 1)]
+    #[case(NonZeroU8::new(5).unwrap(), 3)]
+    #[case(NonZeroU8::MAX, 8)]
+    fn test_bits_needed_nonzero_u8(#[case] input: NonZeroU8, #[case] expected: u32) {
+        assert_eq!(
+            input.bits_needed_to_store(),
+            expected,
+            "Failed for input: {input} (NonZeroU8)"
+        );
+        assert_eq!(
+            input.bits_needed_nonzero().get(),
+            expected,
+            "Failed for input: {input} (NonZeroU8)"
+        );
+    }
+
+    #[rstest]
+    #[case(NonZeroU32::MIN, 1)]
+    #[case(NonZeroU32::new(1023).unwrap(), 10)]
+    #[case(NonZeroU32::new(1024).unwrap(), 11)]
+    #[case(NonZeroU32::MAX, 32)]
+    fn test_bits_needed_nonzero_u32(#[case] input: NonZeroU32, #[case] expected: u32) {
+        assert_eq!(
+            input.bits_needed_to_store(),
+            expected,
+            "Failed for input: {input} (NonZeroU32)"
+        );
+        assert_eq!(
+            input.bits_needed_nonzero().get(),
+            expected,
+            "Failed for input: {input} (NonZeroU32)"
+        );
+    }
+
+    #[rstest]
+    #[case(NonZeroI32::new(-1).unwrap(), 32)]
+    #[case(NonZeroI32::new(1).unwrap(), 1)]
+    #[case(NonZeroI32::MAX, 31)]
+    fn test_bits_needed_nonzero_i32(#[case] input: NonZeroI32, #[case] expected: u32) {
+        assert_eq!(
+            input.bits_needed_to_store(),
+            expected,
+            "Failed for input: {input} (NonZeroI32)"
+        );
+        assert_eq!(
+            input.bits_needed_nonzero().get(),
+            expected,
+            "Failed for input: {input} (NonZeroI32)"
+        );
+    }
+
+    #[test]
+    fn test_bits_needed_nonzero_other_types() {
+        assert_eq!(NonZeroU16::MAX.bits_needed_nonzero().get(), 16);
+        assert_eq!(NonZeroU64::MAX.bits_needed_nonzero().get(), 64);
+        assert_eq!(NonZeroU128::MAX.bits_needed_nonzero().get(), 128);
+        assert_eq!(NonZeroUsize::MIN.bits_needed_nonzero().get(), 1);
+        assert_eq!(NonZeroI8::MIN.bits_needed_to_store(), 8);
+        assert_eq!(NonZeroI16::MAX.bits_needed_to_store(), 15);
+        assert_eq!(NonZeroI64::MIN.bits_needed_to_store(), 64);
+        assert_eq!(NonZeroI128::MAX.bits_needed_to_store(), 127);
+        assert_eq!(NonZeroIsize::MIN.bits_needed_to_store(), isize::BITS);
+    }
+
+    #[rstest]
+    #[case(0u64, 0)]
+    #[case(1u64, 1)]
+    #[case(1024u64, 11)]
+    #[case(u64::MAX, 64)]
+    fn test_bits_needed_wrappers_u64(#[case] input: u64, #[case] expected: u32) {
+        assert_eq!(
+            Wrapping(input).bits_needed_to_store(),
+            expected,
+            "Failed for input: {input} (Wrapping<u64>)"
+        );
+        assert_eq!(
+            Saturating(input).bits_needed_to_store(),
+            expected,
+            "Failed for input: {input} (Saturating<u64>)"
+        );
+    }
+
+    #[test]
+    fn test_bits_needed_wrapping_after_overflow() {
+        let counter = Wrapping(u8::MAX) + Wrapping(2);
+        assert_eq!(counter.bits_needed_to_store(), 1);
+        let counter = Saturating(u8::MAX) + Saturating(2);
+        assert_eq!(counter.bits_needed_to_store(), 8);
+    }
+
+    #[test]
+    fn test_bits_needed_references() {
+        let mut value = 1000u32;
+        assert_eq!(<&u32>::bits_needed_to_store(&&value), 10);
+        assert_eq!(<&&u32>::bits_needed_to_store(&&&value), 10);
+        assert_eq!(<&mut u32>::bits_needed_to_store(&&mut value), 10);
+        assert_eq!(
+            <&Wrapping<u16>>::bits_needed_to_store(&&Wrapping(u16::MAX)),
+            16
+        );
+
+        let ids = [NonZeroU32::new(3).unwrap(), NonZeroU32::new(300).unwrap()];
+        assert_eq!(
+            ids.iter().map(BitsNeeded::bits_needed_to_store).max(),
+            Some(9)
+        );
+    }
+
+    #[rstest]
+    #[case(0u64, 1, 1, 1)]
+    #[case(1u64, 1, 1, 1)]
+    #[case(9u64, 1, 1, 2)]
+    #[case(10u64, 2, 1, 2)]
+    #[case(15u64, 2, 1, 2)]
+    #[case(16u64, 2, 2, 2)]
+    #[case(99u64, 2, 2, 3)]
+    #[case(100u64, 3, 2, 3)]
+    #[case(999_999_999_999_999_999u64, 18, 15, 20)]
+    #[case(1_000_000_000_000_000_000u64, 19, 15, 20)]
+    #[case(u64::MAX, 20, 16, 22)]
+    fn test_digits_needed_u64(
+        #[case] input: u64,
+        #[case] decimal: u32,
+        #[case] hex: u32,
+        #[case] octal: u32,
+    ) {
+        assert_eq!(
+            input.decimal_digits(),
+            decimal,
+            "Failed for input: {input} (u64)"
+        );
+        assert_eq!(input.hex_digits(), hex, "Failed for input: {input} (u64)");
+        assert_eq!(
+            input.digits_in_base(8),
+            octal,
+            "Failed for input: {input} (u64)"
+        );
+        assert_eq!(input.digits_in_base(10), decimal);
+        assert_eq!(input.digits_in_base(16), hex);
+    }
+
+    #[rstest]
+    #[case(0i32, 1)]
+    #[case(-1i32, 2)]
+    #[case(-9i32, 2)]
+    #[case(-10i32, 3)]
+    #[case(i32::MAX, 10)]
+    #[case(i32::MIN, 11)]
+    fn test_digits_needed_i32(#[case] input: i32, #[case] expected: u32) {
+        assert_eq!(
+            input.decimal_digits(),
+            expected,
+            "Failed for input: {input} (i32)"
+        );
+    }
+
+    #[test]
+    fn test_digits_needed_exhaustive_u16() {
+        for value in 0..=u16::MAX {
+            assert_eq!(
+                value.decimal_digits() as usize,
+                alloc::format!("{value}").len(),
+                "Failed for input: {value} (u16)"
+            );
+            assert_eq!(
+                value.hex_digits() as usize,
+                alloc::format!("{value:x}").len(),
+                "Failed for input: {value} (u16)"
+            );
+            assert_eq!(
+                value.digits_in_base(8) as usize,
+                alloc::format!("{value:o}").len(),
+                "Failed for input: {value} (u16)"
+            );
+            assert_eq!(
+                value.digits_in_base(2) as usize,
+                alloc::format!("{value:b}").len(),
+                "Failed for input: {value} (u16)"
+            );
+        }
+    }
+
+    #[test]
+    fn test_digits_needed_exhaustive_i16() {
+        for value in i16::MIN..=i16::MAX {
+            assert_eq!(
+                value.decimal_digits() as usize,
+                alloc::format!("{value}").len(),
+                "Failed for input: {value} (i16)"
+            );
+            let magnitude = alloc::format!("{:x}", value.unsigned_abs()).len();
+            assert_eq!(
+                value.hex_digits() as usize,
+                magnitude + value.is_negative() as usize,
+                "Failed for input: {value} (i16)"
+            );
+        }
+    }
+
+    #[test]
+    fn test_decimal_digits_powers_of_ten() {
+        // Every power of ten and its predecessor, across the table boundaries.
+        let mut power = 1u128;
+        for digits in 1..=39 {
+            assert_eq!(power.decimal_digits(), digits, "Failed for input: {power}");
+            assert_eq!((power - 1).decimal_digits(), (digits - 1).max(1));
+            if let Ok(power) = u64::try_from(power) {
+                assert_eq!(power.decimal_digits(), digits, "Failed for input: {power}");
+                assert_eq!((power - 1).decimal_digits(), (digits - 1).max(1));
+            }
+            power = power.saturating_mul(10);
+        }
+        assert_eq!(u128::MAX.decimal_digits(), 39);
+        assert_eq!(i128::MIN.decimal_digits(), 40);
+        assert_eq!(u8::MAX.decimal_digits(), 3);
+        assert_eq!(i8::MIN.decimal_digits(), 4);
+        assert_eq!(
+            usize::MAX.decimal_digits() as usize,
+            alloc::format!("{}", usize::MAX).len()
+        );
+        assert_eq!(
+            isize::MIN.decimal_digits() as usize,
+            alloc::format!("{}", isize::MIN).len()
+        );
+    }
+
+    #[rstest]
+    #[case(3)]
+    #[case(7)]
+    #[case(12)]
+    #[case(36)]
+    fn test_digits_in_other_bases(#[case] radix: u32) {
+        let values = [0u128, 1, 2, 35, 36, 1295, 1296, u64::MAX as u128, u128::MAX];
+        for value in values {
+            let mut expected = 1;
+            let mut rest = value;
+            while rest >= radix as u128 {
+                rest /= radix as u128;
+                expected += 1;
+            }
+            assert_eq!(
+                value.digits_in_base(radix),
+                expected,
+                "Failed for input: {value} (radix {radix})"
+            );
+            if let Ok(value) = u64::try_from(value) {
+                assert_eq!(value.digits_in_base(radix), expected);
+                assert_eq!(
+                    (value as i128).wrapping_neg().digits_in_base(radix),
+                    expected + (value != 0) as u32
+                );
+            }
+        }
+        assert_eq!(
+            u8::MAX.digits_in_base(radix),
+            (u8::MAX as u128).digits_in_base(radix)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "radix must be in 2..=36")]
+    fn test_digits_in_base_invalid_radix_panics() {
+        10u32.digits_in_base(37);
+    }
+
+    /// Checks `IntLog` and `PowerOfTwo` against naive `u128` definitions, for every value
+    /// produced by `$values` and every power-of-two alignment of the type.
+    macro_rules! test_power_of_two {
+        ($($name:ident: $t:ty => $values:expr),* $(,)?) => {
+            $(
+                #[test]
+                fn $name() {
+                    let max = <$t>::MAX as u128;
+                    let powers = || (0..<$t>::BITS).map(|k| 1u128 << k);
+                    for value in $values {
+                        let wide = value as u128;
+
+                        let ceil = powers().position(|p| p >= wide).map(|k| k as u32);
+                        let expected_ceil = if wide <= 1 { 0 } else { ceil.unwrap_or(<$t>::BITS) };
+                        assert_eq!(value.ilog2_ceil(), expected_ceil, "Failed for input: {value}");
+                        assert_eq!(
+                            value.checked_ilog2_ceil(),
+                            (value != 0).then_some(expected_ceil),
+                            "Failed for input: {value}"
+                        );
+
+                        let prev = powers().filter(|&p| p <= wide).last().map(|p| p as $t);
+                        assert_eq!(value.checked_prev_pow2(), prev, "Failed for input: {value}");
+                        assert_eq!(value.prev_pow2(), prev.unwrap_or(0), "Failed for input: {value}");
+
+                        let next = powers().find(|&p| p >= wide).map(|p| p as $t);
+                        assert_eq!(value.checked_next_pow2(), next, "Failed for input: {value}");
+                        assert_eq!(value.wrapping_next_pow2(), next.unwrap_or(0), "Failed for input: {value}");
+
+                        for align in powers() {
+                            let up = wide.checked_add(align - 1).map(|v| v / align * align).filter(|&v| v <= max);
+                            let down = wide / align * align;
+                            let align = align as $t;
+                            assert_eq!(
+                                value.is_aligned(align),
+                                wide == down,
+                                "Failed for input: {value}, align {align}"
+                            );
+                            assert_eq!(value.align_down(align) as u128, down, "Failed for input: {value}, align {align}");
+                            assert_eq!(value.checked_align_down(align), Some(down as $t));
+                            assert_eq!(
+                                value.checked_align_up(align),
+                                up.map(|v| v as $t),
+                                "Failed for input: {value}, align {align}"
+                            );
+                            assert_eq!(value.wrapping_align_up(align), up.unwrap_or(0) as $t);
+                        }
+
+                        for align in [0, 3, 6, <$t>::MAX] {
+                            assert_eq!(value.checked_align_down(align), None, "Failed for input: {value}, align {align}");
+                            assert_eq!(value.checked_align_up(align), None, "Failed for input: {value}, align {align}");
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    /// Returns every power of two of a `bits`-wide type, its neighbours, and the extremes.
+    fn power_of_two_boundaries(bits: u32) -> alloc::vec::Vec<u128> {
+        let max = u128::MAX >> (128 - bits);
+        let mut values = alloc::vec![0, 1, max - 1, max];
+        for k in 1..bits {
+            values.extend([(1u128 << k) - 1, 1 << k, (1 << k) + 1]);
+        }
+        values
+    }
+
+    test_power_of_two! {
+        test_power_of_two_exhaustive_u8: u8 => 0..=u8::MAX,
+        test_power_of_two_exhaustive_u16: u16 => 0..=u16::MAX,
+        test_power_of_two_boundaries_u32: u32 => power_of_two_boundaries(32).into_iter().map(|v| v as u32),
+        test_power_of_two_boundaries_u64: u64 => power_of_two_boundaries(64).into_iter().map(|v| v as u64),
+        test_power_of_two_boundaries_u128: u128 => power_of_two_boundaries(128),
+        test_power_of_two_boundaries_usize: usize => power_of_two_boundaries(usize::BITS).into_iter().map(|v| v as usize),
+    }
+
+    #[rstest]
+    #[case(13u32, 8, Some(16), 8)]
+    #[case(16u32, 8, Some(16), 16)]
+    #[case(0u32, 4096, Some(0), 0)]
+    #[case(u32::MAX, 1, Some(u32::MAX), u32::MAX)]
+    #[case(u32::MAX, 2, None, u32::MAX - 1)]
+    fn test_align_u32(
+        #[case] input: u32,
+        #[case] align: u32,
+        #[case] up: Option<u32>,
+        #[case] down: u32,
+    ) {
+        assert_eq!(
+            input.checked_align_up(align),
+            up,
+            "Failed for input: {input}, align {align}"
+        );
+        assert_eq!(
+            input.align_down(align),
+            down,
+            "Failed for input: {input}, align {align}"
+        );
+    }
 }
@@ -0,0 +1,457 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A compact id for a string stored in an [`Interner`].
+///
+/// Symbols are handed out sequentially, starting at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Returns the numeric id of this symbol.
+    #[inline]
+    pub const fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the numeric id of this symbol, for use as an index.
+    #[inline]
+    pub const fn as_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A string interner, which maps each distinct string to a [`Symbol`].
+///
+/// All strings are stored back to back in a single buffer, so interning a new string
+/// costs no allocation of its own. The `intern_concat_N` methods hash their parts
+/// incrementally, and only copy them into the interner on a miss, so a key built from
+/// several parts never needs a temporary `String`.
+///
+/// Strings are limited to a combined size of `u32::MAX` bytes.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::interner::Interner;
+/// let mut interner = Interner::new();
+/// let a = interner.intern_concat_3("std", "::", "vec");
+/// let b = interner.intern("std::vec");
+/// assert_eq!(a, b);
+/// assert_eq!(interner.resolve(a), Some("std::vec"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    /// Every interned string, back to back.
+    storage: String,
+    /// End offset of each string in `storage`, indexed by symbol.
+    ends: Vec<u32>,
+    /// Open-addressing hash table; its length is zero or a power of two.
+    table: Vec<Slot>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    hash: u32,
+    symbol: u32,
+}
+
+impl Slot {
+    const EMPTY: Self = Self {
+        hash: 0,
+        symbol: u32::MAX,
+    };
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.symbol == u32::MAX
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+const MIN_TABLE_SIZE: usize = 16;
+
+impl Interner {
+    /// Creates an empty interner. No memory is allocated until the first string is interned.
+    pub const fn new() -> Self {
+        Self {
+            storage: String::new(),
+            ends: Vec::new(),
+            table: Vec::new(),
+        }
+    }
+
+    /// Creates an empty interner with room for `symbols` strings of `bytes` combined length.
+    pub fn with_capacity(symbols: usize, bytes: usize) -> Self {
+        let mut interner = Self {
+            storage: String::with_capacity(bytes),
+            ends: Vec::with_capacity(symbols),
+            table: Vec::new(),
+        };
+        if symbols > 0 {
+            interner.resize_table(table_size_for(symbols));
+        }
+        interner
+    }
+
+    /// Returns the number of distinct strings interned.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Interns `text`, returning its symbol.
+    ///
+    /// # Panics
+    ///
+    /// If the interner's combined size would exceed `u32::MAX` bytes.
+    pub fn intern<S>(&mut self, text: S) -> Symbol
+    where
+        S: AsRef<str>,
+    {
+        self.intern_parts(&[text.as_ref()])
+    }
+
+    /// Interns the concatenation of two strings, returning its symbol.
+    ///
+    /// The parts are only copied into the interner if the concatenation was not interned before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::interner::Interner;
+    /// let mut interner = Interner::new();
+    /// let a = interner.intern_concat_2("foo", "bar");
+    /// let b = interner.intern_concat_2("fo", "obar");
+    /// assert_eq!(a, b);
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the interner's combined size would exceed `u32::MAX` bytes.
+    pub fn intern_concat_2<S1, S2>(&mut self, s1: S1, s2: S2) -> Symbol
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+    {
+        self.intern_parts(&[s1.as_ref(), s2.as_ref()])
+    }
+
+    /// Interns the concatenation of three strings, returning its symbol.
+    ///
+    /// See [`Interner::intern_concat_2`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::interner::Interner;
+    /// let mut interner = Interner::new();
+    /// let symbol = interner.intern_concat_3("core", "::", "mem");
+    /// assert_eq!(interner.resolve(symbol), Some("core::mem"));
+    /// ```
+    pub fn intern_concat_3<S1, S2, S3>(&mut self, s1: S1, s2: S2, s3: S3) -> Symbol
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        S3: AsRef<str>,
+    {
+        self.intern_parts(&[s1.as_ref(), s2.as_ref(), s3.as_ref()])
+    }
+
+    /// Interns the concatenation of four strings, returning its symbol.
+    ///
+    /// See [`Interner::intern_concat_2`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::interner::Interner;
+    /// let mut interner = Interner::new();
+    /// let symbol = interner.intern_concat_4("a", "::", "b", "::c");
+    /// assert_eq!(interner.resolve(symbol), Some("a::b::c"));
+    /// ```
+    pub fn intern_concat_4<S1, S2, S3, S4>(&mut self, s1: S1, s2: S2, s3: S3, s4: S4) -> Symbol
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        S3: AsRef<str>,
+        S4: AsRef<str>,
+    {
+        self.intern_parts(&[s1.as_ref(), s2.as_ref(), s3.as_ref(), s4.as_ref()])
+    }
+
+    /// Interns the concatenation of five strings, returning its symbol.
+    ///
+    /// See [`Interner::intern_concat_2`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::interner::Interner;
+    /// let mut interner = Interner::new();
+    /// let symbol = interner.intern_concat_5("a", "::", "b", "::", "c");
+    /// assert_eq!(interner.resolve(symbol), Some("a::b::c"));
+    /// ```
+    pub fn intern_concat_5<S1, S2, S3, S4, S5>(
+        &mut self,
+        s1: S1,
+        s2: S2,
+        s3: S3,
+        s4: S4,
+        s5: S5,
+    ) -> Symbol
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        S3: AsRef<str>,
+        S4: AsRef<str>,
+        S5: AsRef<str>,
+    {
+        self.intern_parts(&[
+            s1.as_ref(),
+            s2.as_ref(),
+            s3.as_ref(),
+            s4.as_ref(),
+            s5.as_ref(),
+        ])
+    }
+
+    /// Returns the symbol of `text`, if it was interned before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::interner::Interner;
+    /// let mut interner = Interner::new();
+    /// let symbol = interner.intern("a");
+    /// assert_eq!(interner.get("a"), Some(symbol));
+    /// assert_eq!(interner.get("b"), None);
+    /// ```
+    pub fn get<S>(&self, text: S) -> Option<Symbol>
+    where
+        S: AsRef<str>,
+    {
+        let parts = [text.as_ref()];
+        self.find(hash_parts(&parts), &parts).map(Symbol)
+    }
+
+    /// Returns the string for `symbol`, or [`None`] if this interner has no such symbol.
+    #[inline]
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        let index = symbol.as_usize();
+        let end = *self.ends.get(index)? as usize;
+        let start = match index {
+            0 => 0,
+            _ => self.ends[index - 1] as usize,
+        };
+
+        // SAFETY: Both offsets lie on boundaries between whole strings.
+        Some(unsafe { self.storage.get_unchecked(start..end) })
+    }
+
+    /// Returns an iterator over every symbol and its string, in the order they were interned.
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &str)> + '_ {
+        (0..self.ends.len() as u32).map(|x| (Symbol(x), self.resolve(Symbol(x)).unwrap_or("")))
+    }
+
+    fn intern_parts(&mut self, parts: &[&str]) -> Symbol {
+        let hash = hash_parts(parts);
+        if let Some(symbol) = self.find(hash, parts) {
+            return Symbol(symbol);
+        }
+
+        self.insert(hash, parts)
+    }
+
+    /// Finds the symbol whose string equals the concatenation of `parts`.
+    fn find(&self, hash: u32, parts: &[&str]) -> Option<u32> {
+        if self.table.is_empty() {
+            return None;
+        }
+
+        let mask = self.table.len() - 1;
+        let mut pos = hash as usize & mask;
+        loop {
+            let slot = self.table[pos];
+            if slot.is_empty() {
+                return None;
+            }
+
+            if slot.hash == hash && self.symbol_equals(slot.symbol, parts) {
+                return Some(slot.symbol);
+            }
+
+            pos = (pos + 1) & mask;
+        }
+    }
+
+    /// Compares the string for `symbol` against the concatenation of `parts`, without copying them.
+    fn symbol_equals(&self, symbol: u32, parts: &[&str]) -> bool {
+        let Some(mut stored) = self.resolve(Symbol(symbol)).map(str::as_bytes) else {
+            return false;
+        };
+
+        for part in parts {
+            match stored.split_at_checked(part.len()) {
+                Some((head, tail)) if head == part.as_bytes() => stored = tail,
+                _ => return false,
+            }
+        }
+
+        stored.is_empty()
+    }
+
+    fn insert(&mut self, hash: u32, parts: &[&str]) -> Symbol {
+        let total_length: usize = parts.iter().map(|p| p.len()).sum();
+        let end = u32::try_from(self.storage.len() + total_length)
+            .expect("interner storage exceeds u32::MAX bytes");
+
+        let symbol = self.ends.len() as u32;
+        assert!(symbol < u32::MAX, "interner exceeds u32::MAX symbols");
+
+        // Keep the load factor at or below 1/2.
+        if (self.ends.len() + 1) * 2 > self.table.len() {
+            self.resize_table(table_size_for(self.ends.len() + 1));
+        }
+
+        self.storage.reserve(total_length);
+        for part in parts {
+            self.storage.push_str(part);
+        }
+        self.ends.push(end);
+        self.insert_slot(Slot { hash, symbol });
+        Symbol(symbol)
+    }
+
+    fn insert_slot(&mut self, slot: Slot) {
+        let mask = self.table.len() - 1;
+        let mut pos = slot.hash as usize & mask;
+        while !self.table[pos].is_empty() {
+            pos = (pos + 1) & mask;
+        }
+        self.table[pos] = slot;
+    }
+
+    fn resize_table(&mut self, size: usize) {
+        let old = core::mem::replace(&mut self.table, vec![Slot::EMPTY; size]);
+        for slot in old.into_iter().filter(|s| !s.is_empty()) {
+            self.insert_slot(slot);
+        }
+    }
+}
+
+/// Returns a table size which keeps the load factor at or below 1/2 for `symbols` entries.
+#[inline]
+fn table_size_for(symbols: usize) -> usize {
+    (symbols * 2).next_power_of_two().max(MIN_TABLE_SIZE)
+}
+
+/// Hashes the concatenation of `parts`.
+///
+/// This uses FNV-1a, which is byte-at-a-time; so the hash does not depend on where
+/// the string is split into parts.
+#[inline]
+fn hash_parts(parts: &[&str]) -> u32 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for part in parts {
+        for &byte in part.as_bytes() {
+            hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    (hash ^ (hash >> 32)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_intern_deduplicates() {
+        let mut interner = Interner::new();
+        let a = interner.intern("hello");
+        let b = interner.intern(String::from("hello"));
+        let c = interner.intern("world");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.as_u32(), 0);
+        assert_eq!(c.as_u32(), 1);
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn test_intern_concat_matches_whole_string() {
+        let mut interner = Interner::new();
+        let whole = interner.intern("ns::name");
+        assert_eq!(interner.intern_concat_2("ns::", "name"), whole);
+        assert_eq!(interner.intern_concat_3("ns", "::", "name"), whole);
+        assert_eq!(interner.intern_concat_4("", "ns", "::", "name"), whole);
+        assert_eq!(interner.intern_concat_5("n", "s", ":", ":", "name"), whole);
+        assert_eq!(interner.len(), 1);
+        assert_eq!(interner.storage.len(), "ns::name".len());
+    }
+
+    #[test]
+    fn test_intern_concat_prefixes_are_distinct() {
+        let mut interner = Interner::new();
+        let a = interner.intern_concat_2("ab", "c");
+        let b = interner.intern_concat_2("ab", "");
+        let c = interner.intern_concat_2("ab", "cd");
+        let d = interner.intern("");
+        assert_eq!(interner.len(), 4);
+        assert_eq!(interner.resolve(a), Some("abc"));
+        assert_eq!(interner.resolve(b), Some("ab"));
+        assert_eq!(interner.resolve(c), Some("abcd"));
+        assert_eq!(interner.resolve(d), Some(""));
+    }
+
+    #[test]
+    fn test_many_symbols() {
+        let mut interner = Interner::with_capacity(4, 16);
+        let symbols: Vec<Symbol> = (0..10_000)
+            .map(|x| interner.intern_concat_2("key", format!("{x}")))
+            .collect();
+
+        for (x, symbol) in symbols.iter().enumerate() {
+            let key = format!("key{x}");
+            assert_eq!(interner.resolve(*symbol), Some(key.as_str()));
+            assert_eq!(interner.get(&key), Some(*symbol));
+            assert_eq!(symbol.as_usize(), x);
+        }
+
+        assert_eq!(interner.len(), 10_000);
+        assert!(interner.table.len() >= 20_000);
+    }
+
+    #[test]
+    fn test_get_and_resolve_missing() {
+        let mut interner = Interner::default();
+        assert!(interner.is_empty());
+        assert_eq!(interner.get("a"), None);
+        assert_eq!(interner.resolve(Symbol(0)), None);
+
+        interner.intern("a");
+        assert_eq!(interner.get("b"), None);
+        assert_eq!(interner.resolve(Symbol(1)), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut interner = Interner::new();
+        interner.intern("a");
+        interner.intern("b");
+        interner.intern("a");
+        let items: Vec<_> = interner.iter().collect();
+        assert_eq!(items, [(Symbol(0), "a"), (Symbol(1), "b")]);
+    }
+}
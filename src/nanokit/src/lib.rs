@@ -1,12 +1,33 @@
 #![doc = include_str!(concat!("../", env!("CARGO_PKG_README")))]
 #![no_std]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
 #[cfg(feature = "std")]
 extern crate std;
 
 extern crate alloc;
 
+pub mod arena;
+pub mod bitset;
+pub mod bitstream;
+pub mod codes;
 pub mod count_bits;
 #[cfg(feature = "c-exports")]
 pub mod exports;
+pub mod interner;
+#[cfg(feature = "std")]
+pub mod os_concat;
+pub mod packed_vec;
+pub mod path_concat;
+pub mod popcount;
+pub mod prefixed_concat;
+pub mod rank_select;
+pub mod rope;
 pub mod string_concat;
+#[cfg(any(feature = "allocator-api2", feature = "nightly"))]
+pub mod string_concat_in;
 pub mod string_concat_unsafe;
+pub mod string_escape;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod url_concat;
+pub mod varint;
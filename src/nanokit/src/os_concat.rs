@@ -0,0 +1,274 @@
+#[cfg(unix)]
+use alloc::vec::Vec;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+
+/// Concatenates two [`OsStr`] parts into an [`OsString`] with a single allocation.
+///
+/// This is the [`OsStr`] counterpart of [`concat_2`](crate::string_concat::concat_2);
+/// inputs do not need to be valid UTF-8, so no lossy conversion is needed.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::os_concat::os_concat_2;
+/// use std::ffi::OsStr;
+/// let result = os_concat_2("save_", OsStr::new("slot1"));
+/// assert_eq!(result, "save_slot1");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn os_concat_2<S1, S2>(s1: S1, s2: S2) -> OsString
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+{
+    concat_parts(&[s1.as_ref(), s2.as_ref()])
+}
+
+/// Concatenates three [`OsStr`] parts into an [`OsString`] with a single allocation.
+///
+/// See [`os_concat_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::os_concat::os_concat_3;
+/// assert_eq!(os_concat_3("a", "b", "c"), "abc");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn os_concat_3<S1, S2, S3>(s1: S1, s2: S2, s3: S3) -> OsString
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+{
+    concat_parts(&[s1.as_ref(), s2.as_ref(), s3.as_ref()])
+}
+
+/// Concatenates four [`OsStr`] parts into an [`OsString`] with a single allocation.
+///
+/// See [`os_concat_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::os_concat::os_concat_4;
+/// assert_eq!(os_concat_4("a", "b", "c", "d"), "abcd");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn os_concat_4<S1, S2, S3, S4>(s1: S1, s2: S2, s3: S3, s4: S4) -> OsString
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+    S4: AsRef<OsStr>,
+{
+    concat_parts(&[s1.as_ref(), s2.as_ref(), s3.as_ref(), s4.as_ref()])
+}
+
+/// Concatenates five [`OsStr`] parts into an [`OsString`] with a single allocation.
+///
+/// See [`os_concat_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::os_concat::os_concat_5;
+/// assert_eq!(os_concat_5("a", "b", "c", "d", "e"), "abcde");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn os_concat_5<S1, S2, S3, S4, S5>(s1: S1, s2: S2, s3: S3, s4: S4, s5: S5) -> OsString
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+    S4: AsRef<OsStr>,
+    S5: AsRef<OsStr>,
+{
+    concat_parts(&[
+        s1.as_ref(),
+        s2.as_ref(),
+        s3.as_ref(),
+        s4.as_ref(),
+        s5.as_ref(),
+    ])
+}
+
+/// Concatenates two [`OsStr`] parts into a [`PathBuf`] with a single allocation.
+///
+/// Unlike [`join_path_2`](crate::path_concat::join_path_2), no separator is added; this is
+/// for building a single path component, or appending an extension.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::os_concat::path_concat_2;
+/// use std::path::Path;
+/// let stem = Path::new("saves/slot1");
+/// assert_eq!(path_concat_2(stem, ".sav"), Path::new("saves/slot1.sav"));
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn path_concat_2<S1, S2>(s1: S1, s2: S2) -> PathBuf
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+{
+    PathBuf::from(os_concat_2(s1, s2))
+}
+
+/// Concatenates three [`OsStr`] parts into a [`PathBuf`] with a single allocation.
+///
+/// See [`path_concat_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::os_concat::path_concat_3;
+/// use std::path::Path;
+/// assert_eq!(path_concat_3("logs/", "2024", ".txt"), Path::new("logs/2024.txt"));
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn path_concat_3<S1, S2, S3>(s1: S1, s2: S2, s3: S3) -> PathBuf
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+{
+    PathBuf::from(os_concat_3(s1, s2, s3))
+}
+
+/// Concatenates four [`OsStr`] parts into a [`PathBuf`] with a single allocation.
+///
+/// See [`path_concat_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::os_concat::path_concat_4;
+/// use std::path::Path;
+/// assert_eq!(path_concat_4("a/", "b", "/", "c"), Path::new("a/b/c"));
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn path_concat_4<S1, S2, S3, S4>(s1: S1, s2: S2, s3: S3, s4: S4) -> PathBuf
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+    S4: AsRef<OsStr>,
+{
+    PathBuf::from(os_concat_4(s1, s2, s3, s4))
+}
+
+/// Concatenates five [`OsStr`] parts into a [`PathBuf`] with a single allocation.
+///
+/// See [`path_concat_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::os_concat::path_concat_5;
+/// use std::path::Path;
+/// assert_eq!(path_concat_5("a/", "b", "/", "c", ".d"), Path::new("a/b/c.d"));
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn path_concat_5<S1, S2, S3, S4, S5>(s1: S1, s2: S2, s3: S3, s4: S4, s5: S5) -> PathBuf
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+    S4: AsRef<OsStr>,
+    S5: AsRef<OsStr>,
+{
+    PathBuf::from(os_concat_5(s1, s2, s3, s4, s5))
+}
+
+/// Concatenates the parts by copying their encoded bytes into an exact-size buffer.
+#[cfg(unix)]
+#[inline(always)]
+fn concat_parts(parts: &[&OsStr]) -> OsString {
+    let total_length: usize = parts.iter().map(|part| part.len()).sum();
+
+    let mut result = Vec::with_capacity(total_length);
+    unsafe {
+        let mut dst = result.as_mut_ptr();
+        for part in parts {
+            let bytes = part.as_encoded_bytes();
+            core::ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+            dst = dst.add(bytes.len());
+        }
+        result.set_len(total_length);
+
+        // SAFETY: On Unix any byte sequence is a valid `OsStr`.
+        OsString::from_encoded_bytes_unchecked(result)
+    }
+}
+
+/// Concatenates the parts with [`OsString::push`].
+///
+/// Elsewhere (e.g. WTF-8 on Windows), concatenating encoded bytes may not produce valid
+/// encoded bytes, as unpaired surrogates at the edges of parts have to be joined.
+#[cfg(not(unix))]
+#[inline(always)]
+fn concat_parts(parts: &[&OsStr]) -> OsString {
+    let total_length: usize = parts.iter().map(|part| part.len()).sum();
+
+    let mut result = OsString::with_capacity(total_length);
+    for part in parts {
+        result.push(part);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::path::Path;
+
+    #[rstest]
+    #[case("", "", "")]
+    #[case("Hello, ", "world!", "Hello, world!")]
+    #[case("ünï", "cödé", "ünïcödé")]
+    fn test_os_concat_2(#[case] s1: &str, #[case] s2: &str, #[case] expected: &str) {
+        let result = os_concat_2(s1, s2);
+        assert_eq!(result, expected, "Failed for input: {s1:?}, {s2:?}");
+        assert_eq!(result.capacity(), expected.len());
+    }
+
+    #[test]
+    fn test_os_concat_n() {
+        assert_eq!(os_concat_3("a", "", "c"), "ac");
+        assert_eq!(os_concat_4("a", "b", "c", "d"), "abcd");
+        assert_eq!(os_concat_5("", "b", "c", "d", ""), "bcd");
+    }
+
+    #[test]
+    fn test_path_concat_n() {
+        assert_eq!(path_concat_2("a", ".txt"), Path::new("a.txt"));
+        assert_eq!(path_concat_3("a", "/", "b"), Path::new("a/b"));
+        assert_eq!(path_concat_4("a", "/", "b", ".c"), Path::new("a/b.c"));
+        assert_eq!(
+            path_concat_5(Path::new("/x"), "/", "y", "_z", ".bin"),
+            Path::new("/x/y_z.bin")
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[rstest]
+    #[case(b"caf\xe9", b"\xff.bin", b"caf\xe9\xff.bin")]
+    #[case(b"\xc3", b"\xa9", b"\xc3\xa9")]
+    #[case(b"", b"\x80\x80", b"\x80\x80")]
+    fn test_os_concat_non_utf8(#[case] s1: &[u8], #[case] s2: &[u8], #[case] expected: &[u8]) {
+        use std::os::unix::ffi::OsStrExt;
+
+        let result = os_concat_2(OsStr::from_bytes(s1), OsStr::from_bytes(s2));
+        assert_eq!(
+            result.as_bytes(),
+            expected,
+            "Failed for input: {s1:?}, {s2:?}"
+        );
+
+        let result = path_concat_3(OsStr::from_bytes(s1), "/", OsStr::from_bytes(s2));
+        assert_eq!(result.as_os_str().as_bytes(), [s1, b"/", s2].concat());
+    }
+}
@@ -0,0 +1,403 @@
+use crate::bitstream::mask;
+use crate::count_bits::BitsNeeded;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// An array of integers stored at a fixed bit width, packed across `u64` words.
+///
+/// The width is the [`BitsNeeded`] of the largest element, so `[3, 17, 9, 30]` takes 5 bits
+/// per element. Elements may straddle word boundaries; [`PackedVec::get`] reads at most two
+/// words, so random access is O(1).
+///
+/// Pushing or setting a value that doesn't fit the current width repacks the whole array
+/// at the wider width.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::packed_vec::PackedVec;
+/// let mut ids = PackedVec::from_slice(&[3u16, 17, 9, 30]);
+/// assert_eq!(ids.width(), 5);
+/// assert_eq!(ids.get(1), Some(17));
+///
+/// ids.push(1000);
+/// assert_eq!(ids.width(), 10);
+/// assert_eq!(ids.iter().collect::<Vec<_>>(), [3, 17, 9, 30, 1000]);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct PackedVec {
+    words: Vec<u64>,
+    width: u32,
+    len: usize,
+}
+
+impl PackedVec {
+    /// Creates an empty array with a width of 0 bits.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            width: 0,
+            len: 0,
+        }
+    }
+
+    /// Creates an empty array that stores elements at `width` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is greater than 64.
+    #[inline]
+    pub const fn with_width(width: u32) -> Self {
+        assert!(width <= 64, "width must be at most 64 bits");
+        Self {
+            words: Vec::new(),
+            width,
+            len: 0,
+        }
+    }
+
+    /// Packs `values` at the width of the largest one.
+    pub fn from_slice<T>(values: &[T]) -> Self
+    where
+        T: BitsNeeded + Copy + Into<u64>,
+    {
+        let width = values
+            .iter()
+            .map(|value| value.bits_needed_to_store())
+            .max()
+            .unwrap_or(0);
+
+        let mut result = Self::with_width(width);
+        result.words = vec![0; words_needed(values.len(), width)];
+        result.len = values.len();
+        for (x, &value) in values.iter().enumerate() {
+            result.write(x, value.into());
+        }
+        result
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of bits each element is stored in.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the packed words backing the array.
+    #[inline]
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the element at `index`, or [`None`] if it is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<u64> {
+        if index >= self.len {
+            return None;
+        }
+
+        // SAFETY: `index` is in bounds.
+        Some(unsafe { self.get_unchecked(index) })
+    }
+
+    /// Returns the element at `index` without bounds checks.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than [`PackedVec::len`].
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> u64 {
+        if self.width == 0 {
+            return 0;
+        }
+
+        let bit = index * self.width as usize;
+        let word = bit / 64;
+        let offset = (bit % 64) as u32;
+
+        let mut value = *self.words.get_unchecked(word) >> offset;
+        if offset + self.width > 64 {
+            value |= *self.words.get_unchecked(word + 1) << (64 - offset);
+        }
+        value & mask(self.width)
+    }
+
+    /// Replaces the element at `index`, widening the array if `value` doesn't fit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: u64) {
+        assert!(index < self.len, "index {index} out of bounds");
+        self.fit(value);
+        self.write(index, value);
+    }
+
+    /// Appends an element, widening the array if `value` doesn't fit.
+    pub fn push(&mut self, value: u64) {
+        self.fit(value);
+        self.len += 1;
+        self.words.resize(words_needed(self.len, self.width), 0);
+        self.write(self.len - 1, value);
+    }
+
+    /// Returns an iterator over the elements.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            packed: self,
+            index: 0,
+        }
+    }
+
+    /// Unpacks every element into `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not the same length as the array, or if the width is over 32 bits.
+    pub fn unpack_into_u32(&self, out: &mut [u32]) {
+        assert!(self.width <= 32, "elements don't fit in u32");
+        self.unpack_with(out, |value| value as u32);
+    }
+
+    /// Unpacks every element into `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not the same length as the array.
+    pub fn unpack_into_u64(&self, out: &mut [u64]) {
+        self.unpack_with(out, |value| value);
+    }
+
+    #[inline(always)]
+    fn unpack_with<T>(&self, out: &mut [T], convert: impl Fn(u64) -> T) {
+        assert_eq!(out.len(), self.len, "output length must match array length");
+        if self.width == 0 {
+            out.iter_mut().for_each(|value| *value = convert(0));
+            return;
+        }
+
+        // Walk the words sequentially instead of recomputing each position.
+        let width = self.width;
+        let mask = mask(width);
+        let mut word = 0;
+        let mut offset = 0;
+        for value in out {
+            let mut bits = self.words[word] >> offset;
+            if offset + width > 64 {
+                bits |= self.words[word + 1] << (64 - offset);
+            }
+            *value = convert(bits & mask);
+
+            offset += width;
+            if offset >= 64 {
+                offset -= 64;
+                word += 1;
+            }
+        }
+    }
+
+    /// Widens the array so `value` fits, repacking every element.
+    #[inline]
+    fn fit(&mut self, value: u64) {
+        let needed = value.bits_needed_to_store();
+        if needed > self.width {
+            self.repack(needed);
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn repack(&mut self, width: u32) {
+        let mut result = Self::with_width(width);
+        result.words = vec![0; words_needed(self.len, width)];
+        result.len = self.len;
+        for (x, value) in self.iter().enumerate() {
+            result.write(x, value);
+        }
+        *self = result;
+    }
+
+    /// Writes `value`, which must fit in the width, to the in-bounds element at `index`.
+    #[inline]
+    fn write(&mut self, index: usize, value: u64) {
+        if self.width == 0 {
+            return;
+        }
+
+        let bit = index * self.width as usize;
+        let word = bit / 64;
+        let offset = (bit % 64) as u32;
+        let mask = mask(self.width);
+
+        self.words[word] = (self.words[word] & !(mask << offset)) | (value << offset);
+        if offset + self.width > 64 {
+            let high = 64 - offset;
+            self.words[word + 1] = (self.words[word + 1] & !(mask >> high)) | (value >> high);
+        }
+    }
+}
+
+impl fmt::Debug for PackedVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a PackedVec {
+    type Item = u64;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`PackedVec`], created by [`PackedVec::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    packed: &'a PackedVec,
+    index: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<u64> {
+        let value = self.packed.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.packed.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// Returns the number of words needed for `len` elements of `width` bits.
+#[inline(always)]
+fn words_needed(len: usize, width: u32) -> usize {
+    (len * width as usize).div_ceil(64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[], 0)]
+    #[case(&[0, 0, 0], 0)]
+    #[case(&[1, 0, 1], 1)]
+    #[case(&[3, 17, 9, 30], 5)]
+    #[case(&[u64::MAX, 1], 64)]
+    #[case(&[1 << 40, 5, 7], 41)]
+    fn test_from_slice(#[case] values: &[u64], #[case] width: u32) {
+        let packed = PackedVec::from_slice(values);
+        assert_eq!(packed.width(), width, "Failed for input: {values:?}");
+        assert_eq!(packed.len(), values.len());
+        assert_eq!(packed.as_words().len(), words_needed(values.len(), width));
+        for (x, &value) in values.iter().enumerate() {
+            assert_eq!(packed.get(x), Some(value), "Failed for input: {values:?}");
+        }
+        assert_eq!(packed.get(values.len()), None);
+        assert!(packed.iter().eq(values.iter().copied()));
+    }
+
+    #[test]
+    fn test_every_width_straddles_words() {
+        for width in 1..=64u32 {
+            let max = mask(width);
+            let values: Vec<u64> = (0..200u64)
+                .map(|x| x.wrapping_mul(0x9E37_79B9_7F4A_7C15) & max)
+                .chain([max])
+                .collect();
+
+            let packed = PackedVec::from_slice(&values);
+            assert_eq!(packed.width(), width);
+            assert!(
+                packed.iter().eq(values.iter().copied()),
+                "Failed for width: {width}"
+            );
+
+            let mut unpacked = vec![0u64; values.len()];
+            packed.unpack_into_u64(&mut unpacked);
+            assert_eq!(unpacked, values, "Failed for width: {width}");
+        }
+    }
+
+    #[test]
+    fn test_set_keeps_neighbours() {
+        let mut packed = PackedVec::from_slice(&[0x7Fu8; 20]);
+        for x in (0..20).step_by(3) {
+            packed.set(x, 0);
+        }
+        for x in 0..20 {
+            let expected = if x % 3 == 0 { 0 } else { 0x7F };
+            assert_eq!(packed.get(x), Some(expected), "Failed for index: {x}");
+        }
+    }
+
+    #[test]
+    fn test_push_and_set_widen() {
+        let mut packed = PackedVec::new();
+        for x in 0..100u64 {
+            packed.push(x);
+        }
+        assert_eq!(packed.width(), 7);
+
+        packed.set(50, 1 << 20);
+        assert_eq!(packed.width(), 21);
+        assert_eq!(packed.get(50), Some(1 << 20));
+        assert_eq!(packed.get(99), Some(99));
+        assert_eq!(packed.as_words().len(), words_needed(100, 21));
+
+        packed.push(0);
+        assert_eq!(packed.len(), 101);
+        assert_eq!(packed.iter().len(), 101);
+    }
+
+    #[test]
+    fn test_unpack_into_u32() {
+        let values: Vec<u32> = (0..1000).map(|x| x * 31).collect();
+        let packed = PackedVec::from_slice(&values);
+        let mut unpacked = vec![0u32; values.len()];
+        packed.unpack_into_u32(&mut unpacked);
+        assert_eq!(unpacked, values);
+
+        let zeroes = PackedVec::from_slice(&[0u32; 5]);
+        let mut unpacked = [1u32; 5];
+        zeroes.unpack_into_u32(&mut unpacked);
+        assert_eq!(unpacked, [0; 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "output length")]
+    fn test_unpack_length_mismatch_panics() {
+        PackedVec::from_slice(&[1u8, 2]).unpack_into_u64(&mut [0; 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "don't fit in u32")]
+    fn test_unpack_too_wide_panics() {
+        PackedVec::from_slice(&[u64::MAX]).unpack_into_u32(&mut [0]);
+    }
+}
@@ -0,0 +1,612 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{ffi::OsStr, ffi::OsString, path::PathBuf};
+
+/// Joins two path segments with exactly one `/` between them.
+///
+/// Separators at the boundary between two segments are collapsed into a single `/`,
+/// so `dir` may or may not end in a separator. Empty segments are skipped.
+/// The leading separator of the first segment and the trailing separator of the last
+/// segment are preserved.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_2;
+/// assert_eq!(join_2("assets", "player.png"), "assets/player.png");
+/// assert_eq!(join_2("assets/", "player.png"), "assets/player.png");
+/// assert_eq!(join_2("/", "/player.png"), "/player.png");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_2<S1, S2>(s1: S1, s2: S2) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+{
+    join_slice(&[s1.as_ref(), s2.as_ref()])
+}
+
+/// Joins three path segments with exactly one `/` between each of them.
+///
+/// See [`join_2`] for how separators are handled.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_3;
+/// assert_eq!(join_3("data/", "/maps/", "level1.bin"), "data/maps/level1.bin");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_3<S1, S2, S3>(s1: S1, s2: S2, s3: S3) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+{
+    join_slice(&[s1.as_ref(), s2.as_ref(), s3.as_ref()])
+}
+
+/// Joins four path segments with exactly one `/` between each of them.
+///
+/// See [`join_2`] for how separators are handled.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_4;
+/// assert_eq!(join_4("/mnt", "data", "maps/", "level1.bin"), "/mnt/data/maps/level1.bin");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_4<S1, S2, S3, S4>(s1: S1, s2: S2, s3: S3, s4: S4) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+    S4: AsRef<str>,
+{
+    join_slice(&[s1.as_ref(), s2.as_ref(), s3.as_ref(), s4.as_ref()])
+}
+
+/// Joins five path segments with exactly one `/` between each of them.
+///
+/// See [`join_2`] for how separators are handled.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_5;
+/// assert_eq!(join_5("a/", "/b/", "c", "", "d"), "a/b/c/d");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_5<S1, S2, S3, S4, S5>(s1: S1, s2: S2, s3: S3, s4: S4, s5: S5) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+    S4: AsRef<str>,
+    S5: AsRef<str>,
+{
+    join_slice(&[
+        s1.as_ref(),
+        s2.as_ref(),
+        s3.as_ref(),
+        s4.as_ref(),
+        s5.as_ref(),
+    ])
+}
+
+/// Joins any number of path segments with exactly one `/` between each of them.
+///
+/// The exact length of the result is computed up front, so this performs a single allocation.
+/// See [`join_2`] for how separators are handled.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_slice;
+/// assert_eq!(join_slice(&["usr/", "local", "/bin"]), "usr/local/bin");
+/// assert_eq!(join_slice::<&str>(&[]), "");
+/// ```
+pub fn join_slice<S>(segments: &[S]) -> String
+where
+    S: AsRef<str>,
+{
+    let bytes = join_bytes(segments.iter().map(|s| s.as_ref().as_bytes()));
+
+    // SAFETY: Inputs are valid UTF-8 and are only ever split around ASCII `/`.
+    unsafe { String::from_utf8_unchecked(bytes) }
+}
+
+/// Joins two path segments with exactly one `/` between them, resolving `.` and `..`.
+///
+/// Runs of separators anywhere in the input are collapsed, `.` segments are dropped and
+/// `..` removes the preceding segment. For absolute paths, `..` at the root is dropped;
+/// for relative paths, leading `..` segments are kept. A trailing separator is not kept.
+/// If nothing remains of a relative path, the result is an empty string.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_normalized_2;
+/// assert_eq!(join_normalized_2("mods/./textures/", "../sounds/hit.wav"), "mods/sounds/hit.wav");
+/// assert_eq!(join_normalized_2("/", "../etc"), "/etc");
+/// assert_eq!(join_normalized_2("..", "a"), "../a");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_normalized_2<S1, S2>(s1: S1, s2: S2) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+{
+    join_normalized_slice(&[s1.as_ref(), s2.as_ref()])
+}
+
+/// Joins three path segments with exactly one `/` between each of them, resolving `.` and `..`.
+///
+/// See [`join_normalized_2`] for how segments are resolved.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_normalized_3;
+/// assert_eq!(join_normalized_3("a/b", "..", "c"), "a/c");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_normalized_3<S1, S2, S3>(s1: S1, s2: S2, s3: S3) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+{
+    join_normalized_slice(&[s1.as_ref(), s2.as_ref(), s3.as_ref()])
+}
+
+/// Joins four path segments with exactly one `/` between each of them, resolving `.` and `..`.
+///
+/// See [`join_normalized_2`] for how segments are resolved.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_normalized_4;
+/// assert_eq!(join_normalized_4("/a", "./b", "../..", "c"), "/c");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_normalized_4<S1, S2, S3, S4>(s1: S1, s2: S2, s3: S3, s4: S4) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+    S4: AsRef<str>,
+{
+    join_normalized_slice(&[s1.as_ref(), s2.as_ref(), s3.as_ref(), s4.as_ref()])
+}
+
+/// Joins five path segments with exactly one `/` between each of them, resolving `.` and `..`.
+///
+/// See [`join_normalized_2`] for how segments are resolved.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_normalized_5;
+/// assert_eq!(join_normalized_5("a", "b", "..", "..", ".."), "..");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_normalized_5<S1, S2, S3, S4, S5>(s1: S1, s2: S2, s3: S3, s4: S4, s5: S5) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+    S4: AsRef<str>,
+    S5: AsRef<str>,
+{
+    join_normalized_slice(&[
+        s1.as_ref(),
+        s2.as_ref(),
+        s3.as_ref(),
+        s4.as_ref(),
+        s5.as_ref(),
+    ])
+}
+
+/// Joins any number of path segments with exactly one `/` between each of them,
+/// resolving `.` and `..`.
+///
+/// This performs a single allocation, sized for the unresolved input; resolving `..`
+/// can only shrink the result. See [`join_normalized_2`] for how segments are resolved.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_normalized_slice;
+/// assert_eq!(join_normalized_slice(&["/srv//www", "./", "site/../index.html"]), "/srv/www/index.html");
+/// ```
+pub fn join_normalized_slice<S>(segments: &[S]) -> String
+where
+    S: AsRef<str>,
+{
+    let bytes = join_normalized_bytes(segments.iter().map(|s| s.as_ref().as_bytes()));
+
+    // SAFETY: Inputs are valid UTF-8 and are only ever split around ASCII `/`.
+    unsafe { String::from_utf8_unchecked(bytes) }
+}
+
+/// Joins two [`OsStr`] path segments into a [`PathBuf`] with exactly one `/` between them.
+///
+/// This behaves like [`join_2`], but operates on the platform's encoded bytes,
+/// so inputs do not need to be valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_path_2;
+/// use std::path::Path;
+/// assert_eq!(join_path_2("assets/", "player.png"), Path::new("assets/player.png"));
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_path_2<S1, S2>(s1: S1, s2: S2) -> PathBuf
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+{
+    join_path_slice(&[s1.as_ref(), s2.as_ref()])
+}
+
+/// Joins three [`OsStr`] path segments into a [`PathBuf`] with exactly one `/` between each of them.
+///
+/// See [`join_path_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_path_3;
+/// use std::path::Path;
+/// assert_eq!(join_path_3("data/", "/maps/", "level1.bin"), Path::new("data/maps/level1.bin"));
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_path_3<S1, S2, S3>(s1: S1, s2: S2, s3: S3) -> PathBuf
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+{
+    join_path_slice(&[s1.as_ref(), s2.as_ref(), s3.as_ref()])
+}
+
+/// Joins four [`OsStr`] path segments into a [`PathBuf`] with exactly one `/` between each of them.
+///
+/// See [`join_path_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_path_4;
+/// use std::path::Path;
+/// assert_eq!(join_path_4("/mnt", "data", "maps/", "level1.bin"), Path::new("/mnt/data/maps/level1.bin"));
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_path_4<S1, S2, S3, S4>(s1: S1, s2: S2, s3: S3, s4: S4) -> PathBuf
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+    S4: AsRef<OsStr>,
+{
+    join_path_slice(&[s1.as_ref(), s2.as_ref(), s3.as_ref(), s4.as_ref()])
+}
+
+/// Joins five [`OsStr`] path segments into a [`PathBuf`] with exactly one `/` between each of them.
+///
+/// See [`join_path_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_path_5;
+/// use std::path::Path;
+/// assert_eq!(join_path_5("a/", "/b/", "c", "", "d"), Path::new("a/b/c/d"));
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_path_5<S1, S2, S3, S4, S5>(s1: S1, s2: S2, s3: S3, s4: S4, s5: S5) -> PathBuf
+where
+    S1: AsRef<OsStr>,
+    S2: AsRef<OsStr>,
+    S3: AsRef<OsStr>,
+    S4: AsRef<OsStr>,
+    S5: AsRef<OsStr>,
+{
+    join_path_slice(&[
+        s1.as_ref(),
+        s2.as_ref(),
+        s3.as_ref(),
+        s4.as_ref(),
+        s5.as_ref(),
+    ])
+}
+
+/// Joins any number of [`OsStr`] path segments into a [`PathBuf`] with exactly one `/`
+/// between each of them.
+///
+/// See [`join_path_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_path_slice;
+/// use std::path::Path;
+/// assert_eq!(join_path_slice(&["usr/", "local", "/bin"]), Path::new("usr/local/bin"));
+/// ```
+#[cfg(feature = "std")]
+pub fn join_path_slice<S>(segments: &[S]) -> PathBuf
+where
+    S: AsRef<OsStr>,
+{
+    let bytes = join_bytes(segments.iter().map(|s| s.as_ref().as_encoded_bytes()));
+
+    // SAFETY: Inputs are valid encoded bytes and are only ever split around ASCII `/`.
+    PathBuf::from(unsafe { OsString::from_encoded_bytes_unchecked(bytes) })
+}
+
+/// Joins any number of [`OsStr`] path segments into a [`PathBuf`] with exactly one `/`
+/// between each of them, resolving `.` and `..`.
+///
+/// This behaves like [`join_normalized_slice`], but operates on the platform's encoded bytes.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::path_concat::join_path_normalized_slice;
+/// use std::path::Path;
+/// assert_eq!(join_path_normalized_slice(&["mods/textures", "../sounds"]), Path::new("mods/sounds"));
+/// ```
+#[cfg(feature = "std")]
+pub fn join_path_normalized_slice<S>(segments: &[S]) -> PathBuf
+where
+    S: AsRef<OsStr>,
+{
+    let bytes = join_normalized_bytes(segments.iter().map(|s| s.as_ref().as_encoded_bytes()));
+
+    // SAFETY: Inputs are valid encoded bytes and are only ever split around ASCII `/`.
+    PathBuf::from(unsafe { OsString::from_encoded_bytes_unchecked(bytes) })
+}
+
+/// Joins the segments into a buffer of the exact required length.
+fn join_bytes<'a, I>(segments: I) -> Vec<u8>
+where
+    I: Iterator<Item = &'a [u8]> + Clone,
+{
+    // Sizing pass, then a copy pass; same approach as `string_concat`.
+    let mut total_length = 0;
+    for_each_piece(segments.clone(), |piece| total_length += piece.len());
+
+    let mut result: Vec<u8> = Vec::with_capacity(total_length);
+    unsafe {
+        let mut pos = 0;
+        let dst = result.as_mut_ptr();
+        for_each_piece(segments, |piece| {
+            core::ptr::copy_nonoverlapping(piece.as_ptr(), dst.add(pos), piece.len());
+            pos += piece.len();
+        });
+        result.set_len(total_length);
+    }
+
+    result
+}
+
+/// Invokes `emit` for every piece of the joined path, in order.
+///
+/// Separator runs at segment boundaries are trimmed, and a single `/` is emitted in their place.
+fn for_each_piece<'a, I>(segments: I, mut emit: impl FnMut(&'a [u8]))
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    let mut started = false;
+    let mut trailing = false;
+
+    for segment in segments {
+        if segment.is_empty() {
+            continue;
+        }
+
+        let segment = if started {
+            trim_start_separators(segment)
+        } else {
+            segment
+        };
+
+        let core = trim_end_separators(segment);
+        if core.is_empty() {
+            // Segment was made up entirely of separators.
+            trailing = true;
+        } else {
+            if started {
+                emit(b"/");
+            }
+            emit(core);
+            trailing = core.len() != segment.len();
+        }
+
+        started = true;
+    }
+
+    if trailing {
+        emit(b"/");
+    }
+}
+
+/// Joins and normalizes the segments into a buffer sized for the unresolved input.
+fn join_normalized_bytes<'a, I>(segments: I) -> Vec<u8>
+where
+    I: Iterator<Item = &'a [u8]> + Clone,
+{
+    // Every component in a segment is followed by at most one separator,
+    // so this is an upper bound for the result.
+    let capacity = segments.clone().map(|s| s.len() + 1).sum();
+    let mut result = Vec::with_capacity(capacity);
+
+    let mut segments = segments.skip_while(|s| s.is_empty()).peekable();
+    let absolute = segments
+        .peek()
+        .is_some_and(|first| first.first() == Some(&b'/'));
+
+    if absolute {
+        result.push(b'/');
+    }
+
+    // Length of the prefix that `..` cannot remove; the root, or leading `..` of relative paths.
+    let root = result.len();
+    let mut fixed = root;
+
+    for segment in segments {
+        for component in segment.split(|&b| b == b'/') {
+            match component {
+                b"" | b"." => {}
+                b".." if result.len() > fixed => {
+                    let last_separator = result[fixed..].iter().rposition(|&b| b == b'/');
+                    result.truncate(last_separator.map_or(fixed, |pos| fixed + pos));
+                }
+                b".." if absolute => {}
+                _ => {
+                    if result.len() > root {
+                        result.push(b'/');
+                    }
+                    result.extend_from_slice(component);
+                    if component == b".." {
+                        fixed = result.len();
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+#[inline]
+fn trim_start_separators(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != b'/').unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+#[inline]
+fn trim_end_separators(bytes: &[u8]) -> &[u8] {
+    let end = bytes
+        .iter()
+        .rposition(|&b| b != b'/')
+        .map_or(0, |pos| pos + 1);
+    &bytes[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[], "")]
+    #[case(&["", ""], "")]
+    #[case(&["a", "b"], "a/b")]
+    #[case(&["a/", "b"], "a/b")]
+    #[case(&["a", "/b"], "a/b")]
+    #[case(&["a//", "//b"], "a/b")]
+    #[case(&["a", "", "b"], "a/b")]
+    #[case(&["a", "/", "b"], "a/b")]
+    #[case(&["/", "a"], "/a")]
+    #[case(&["//", "a"], "/a")]
+    #[case(&["/a", "b/"], "/a/b/")]
+    #[case(&["a", "/"], "a/")]
+    #[case(&["/", "/"], "/")]
+    #[case(&["", "/a"], "/a")]
+    #[case(&["a//b", "c"], "a//b/c")]
+    #[case(&["./a", "../b"], "./a/../b")]
+    #[case(&["ü/", "日本"], "ü/日本")]
+    fn test_join_slice(#[case] segments: &[&str], #[case] expected: &str) {
+        let result = join_slice(segments);
+        assert_eq!(result, expected, "Failed for segments: {segments:?}");
+        assert_eq!(result.capacity(), result.len());
+    }
+
+    #[rstest]
+    #[case(&[], "")]
+    #[case(&["a", "b"], "a/b")]
+    #[case(&["a//b/", "//c"], "a/b/c")]
+    #[case(&["a/./b", "."], "a/b")]
+    #[case(&["a/b", ".."], "a")]
+    #[case(&["a/b", "../.."], "")]
+    #[case(&["a", "../.."], "..")]
+    #[case(&["..", "a"], "../a")]
+    #[case(&["..", "a", ".."], "..")]
+    #[case(&["../..", "a", "..", ".."], "../../..")]
+    #[case(&["/a", "../.."], "/")]
+    #[case(&["/..", "a"], "/a")]
+    #[case(&["", "/a/", "b/"], "/a/b")]
+    #[case(&["/", ""], "/")]
+    #[case(&["a", "..", "b"], "b")]
+    #[case(&["...", "..a"], ".../..a")]
+    fn test_join_normalized_slice(#[case] segments: &[&str], #[case] expected: &str) {
+        assert_eq!(
+            join_normalized_slice(segments),
+            expected,
+            "Failed for segments: {segments:?}"
+        );
+    }
+
+    #[test]
+    fn test_join_n_matches_join_slice() {
+        assert_eq!(join_2("a/", "b"), join_slice(&["a/", "b"]));
+        assert_eq!(join_3("a", "/b/", "c"), join_slice(&["a", "/b/", "c"]));
+        assert_eq!(join_4("a", "b", "c", "d"), "a/b/c/d");
+        assert_eq!(join_5("a", "b", "c", "d", "e/"), "a/b/c/d/e/");
+    }
+
+    #[test]
+    fn test_join_n_string_objects() {
+        let dir = String::from("assets/");
+        let file = "player.png".to_string();
+        assert_eq!(join_2(dir, file), "assets/player.png");
+    }
+
+    #[test]
+    fn test_join_normalized_n() {
+        assert_eq!(join_normalized_2("a/b/", "../c"), "a/c");
+        assert_eq!(join_normalized_3("/a", "..", "..",), "/");
+        assert_eq!(join_normalized_4("a", ".", "b", "./"), "a/b");
+        assert_eq!(join_normalized_5("a", "b", "c", "../..", "d"), "a/d");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_join_path() {
+        use std::path::Path;
+
+        assert_eq!(join_path_2("a/", "/b"), Path::new("a/b"));
+        assert_eq!(join_path_3("/", "a", "b/"), Path::new("/a/b/"));
+        assert_eq!(join_path_4("a", "", "b", "c"), Path::new("a/b/c"));
+        assert_eq!(join_path_5("a", "b", "c", "d", "e"), Path::new("a/b/c/d/e"));
+        assert_eq!(
+            join_path_normalized_slice(&["/a/b", "../c"]),
+            Path::new("/a/c")
+        );
+    }
+
+    #[cfg(all(feature = "std", unix))]
+    #[test]
+    fn test_join_path_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = OsStr::from_bytes(b"caf\xe9/");
+        let file = OsStr::from_bytes(b"\xff.bin");
+        let result = join_path_2(dir, file);
+        assert_eq!(result.as_os_str().as_bytes(), b"caf\xe9/\xff.bin");
+
+        let result = join_path_normalized_slice(&[dir, OsStr::new(".."), file]);
+        assert_eq!(result.as_os_str().as_bytes(), b"\xff.bin");
+    }
+}
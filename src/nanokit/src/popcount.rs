@@ -0,0 +1,398 @@
+/// Inputs with at least this many words use Harley–Seal when there is no popcount instruction.
+const HARLEY_SEAL_MIN_WORDS: usize = 64;
+
+/// A trait to count set bits across a whole slice of numbers at once.
+///
+/// Use the [`count_ones_slice`] and [`hamming_distance`] functions to call it.
+///
+/// On `x86_64` with the `std` feature, the CPU is checked for the `popcnt` instruction at
+/// runtime; other targets that always have one (`aarch64`, or `x86_64` built with
+/// `target-feature=+popcnt`) use it directly. Without one, long inputs are counted with the
+/// Harley–Seal carry-save adder, which needs a single software popcount per 16 words.
+pub trait Popcount: Sized {
+    /// Returns the number of set bits in `values`.
+    fn count_ones_slice(values: &[Self]) -> u64;
+
+    /// Returns the number of bits that differ between `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` and `b` have different lengths.
+    fn hamming_distance(a: &[Self], b: &[Self]) -> u64;
+}
+
+/// Returns the number of set bits in `values`.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::popcount::count_ones_slice;
+/// assert_eq!(count_ones_slice(&[0b1011u64, u64::MAX]), 67);
+/// assert_eq!(count_ones_slice(b"\x01\x03\xff"), 11);
+/// ```
+#[inline]
+pub fn count_ones_slice<T: Popcount>(values: &[T]) -> u64 {
+    T::count_ones_slice(values)
+}
+
+/// Returns the number of bits that differ between `a` and `b`.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::popcount::hamming_distance;
+/// assert_eq!(hamming_distance(&[0b1100u64], &[0b1010]), 2);
+/// assert_eq!(hamming_distance(b"karolin", b"kathrin"), 9);
+/// ```
+#[inline]
+pub fn hamming_distance<T: Popcount>(a: &[T], b: &[T]) -> u64 {
+    T::hamming_distance(a, b)
+}
+
+/// Returns the number of set bits in bits `start..end` of `bits`, where bit `i` is bit
+/// `i % 64` of word `i / 64`.
+///
+/// # Panics
+///
+/// Panics if `start` is greater than `end`, or `end` is greater than `bits.len() * 64`.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::popcount::count_ones_range;
+/// let bits = [u64::MAX, 0b1111];
+/// assert_eq!(count_ones_range(&bits, 60, 66), 6);
+/// assert_eq!(count_ones_range(&bits, 66, 128), 2);
+/// ```
+pub fn count_ones_range(bits: &[u64], start: usize, end: usize) -> u64 {
+    assert!(start <= end, "range start {start} is after its end {end}");
+    assert!(
+        end <= bits.len() * 64,
+        "range end {end} out of bounds for {} bits",
+        bits.len() * 64
+    );
+    if start == end {
+        return 0;
+    }
+
+    let first = start / 64;
+    let last = (end - 1) / 64;
+    let low_mask = u64::MAX << (start % 64);
+    let high_mask = u64::MAX >> (63 - (end - 1) % 64);
+    if first == last {
+        return (bits[first] & low_mask & high_mask).count_ones() as u64;
+    }
+
+    (bits[first] & low_mask).count_ones() as u64
+        + count_ones_slice(&bits[first + 1..last])
+        + (bits[last] & high_mask).count_ones() as u64
+}
+
+impl Popcount for u64 {
+    fn count_ones_slice(values: &[Self]) -> u64 {
+        // SAFETY: `count_words` only loads indices below the length.
+        count_words(values.len(), |x| unsafe { *values.get_unchecked(x) })
+    }
+
+    fn hamming_distance(a: &[Self], b: &[Self]) -> u64 {
+        assert_eq!(a.len(), b.len(), "slices must have the same length");
+        // SAFETY: `count_words` only loads indices below the length, which both slices share.
+        count_words(a.len(), |x| unsafe {
+            *a.get_unchecked(x) ^ *b.get_unchecked(x)
+        })
+    }
+}
+
+impl Popcount for u8 {
+    fn count_ones_slice(values: &[Self]) -> u64 {
+        let tail = &values[values.len() / 8 * 8..];
+        // SAFETY: `count_words` only loads groups of 8 bytes that are in bounds.
+        count_words(values.len() / 8, |x| unsafe { load_word(values, x) })
+            + tail
+                .iter()
+                .map(|byte| byte.count_ones() as u64)
+                .sum::<u64>()
+    }
+
+    fn hamming_distance(a: &[Self], b: &[Self]) -> u64 {
+        assert_eq!(a.len(), b.len(), "slices must have the same length");
+        let start = a.len() / 8 * 8;
+        // SAFETY: `count_words` only loads groups of 8 bytes that are in bounds of both slices.
+        count_words(a.len() / 8, |x| unsafe {
+            load_word(a, x) ^ load_word(b, x)
+        }) + a[start..]
+            .iter()
+            .zip(&b[start..])
+            .map(|(a, b)| (a ^ b).count_ones() as u64)
+            .sum::<u64>()
+    }
+}
+
+/// Loads the `index`th group of 8 bytes as a word.
+///
+/// # Safety
+///
+/// `index * 8 + 8` must not be greater than `bytes.len()`.
+#[inline(always)]
+unsafe fn load_word(bytes: &[u8], index: usize) -> u64 {
+    bytes.as_ptr().add(index * 8).cast::<u64>().read_unaligned()
+}
+
+/// Counts the set bits of the `len` words returned by `load`.
+///
+/// `load` is only called with indices less than `len`, so callers may skip bounds checks; the
+/// kernels are passed the length and loader separately, which hides the checks from the
+/// optimizer.
+#[cfg(any(target_feature = "popcnt", target_arch = "aarch64"))]
+#[inline(always)]
+fn count_words(len: usize, load: impl Fn(usize) -> u64) -> u64 {
+    count_words_native(len, load)
+}
+
+/// Counts the set bits of the `len` words returned by `load`.
+///
+/// See the version above for the contract on `load`.
+#[cfg(not(any(target_feature = "popcnt", target_arch = "aarch64")))]
+#[inline(always)]
+fn count_words(len: usize, load: impl Fn(usize) -> u64) -> u64 {
+    #[cfg(all(feature = "std", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("popcnt") {
+        // SAFETY: The CPU supports `popcnt`.
+        return unsafe { count_words_popcnt(len, load) };
+    }
+    count_words_portable(len, load)
+}
+
+/// [`count_words_native`], compiled to use the `popcnt` instruction.
+#[cfg(all(
+    feature = "std",
+    target_arch = "x86_64",
+    not(target_feature = "popcnt")
+))]
+#[target_feature(enable = "popcnt")]
+unsafe fn count_words_popcnt(len: usize, load: impl Fn(usize) -> u64) -> u64 {
+    count_words_native(len, load)
+}
+
+/// Counts each word with [`u64::count_ones`], in 4 independent sums.
+#[inline(always)]
+fn count_words_native(len: usize, load: impl Fn(usize) -> u64) -> u64 {
+    let mut sums = [0u64; 4];
+    let mut x = 0;
+    while x + 4 <= len {
+        for (lane, sum) in sums.iter_mut().enumerate() {
+            *sum += load(x + lane).count_ones() as u64;
+        }
+        x += 4;
+    }
+    for x in x..len {
+        sums[0] += load(x).count_ones() as u64;
+    }
+    sums.iter().sum()
+}
+
+/// Counts the words without relying on a popcount instruction.
+#[cfg_attr(
+    any(target_feature = "popcnt", target_arch = "aarch64"),
+    allow(dead_code)
+)]
+#[inline(always)]
+fn count_words_portable(len: usize, load: impl Fn(usize) -> u64) -> u64 {
+    if len >= HARLEY_SEAL_MIN_WORDS {
+        harley_seal(len, load)
+    } else {
+        count_words_native(len, load)
+    }
+}
+
+/// Carry-save adder: returns the carry and sum bits of adding `a`, `b` and `c` bitwise.
+#[inline(always)]
+fn csa(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let u = a ^ b;
+    ((a & b) | (u & c), u ^ c)
+}
+
+/// Counts the words with the Harley–Seal algorithm.
+///
+/// A tree of carry-save adders sums 16 words into bit-sliced counters for the ones, twos,
+/// fours and eights, and only the carry out of the eights (the sixteens) is popcounted per
+/// block. The counters are popcounted once at the end.
+#[cfg_attr(
+    any(target_feature = "popcnt", target_arch = "aarch64"),
+    allow(dead_code)
+)]
+#[inline(always)]
+fn harley_seal(len: usize, load: impl Fn(usize) -> u64) -> u64 {
+    let (mut ones, mut twos, mut fours, mut eights) = (0, 0, 0, 0);
+    let mut sixteens_total = 0u64;
+
+    let blocks = len / 16;
+    for block in 0..blocks {
+        let word = |x: usize| load(block * 16 + x);
+
+        let (twos_a, sum) = csa(ones, word(0), word(1));
+        let (twos_b, sum) = csa(sum, word(2), word(3));
+        let (fours_a, twos_sum) = csa(twos, twos_a, twos_b);
+        let (twos_a, sum) = csa(sum, word(4), word(5));
+        let (twos_b, sum) = csa(sum, word(6), word(7));
+        let (fours_b, twos_sum) = csa(twos_sum, twos_a, twos_b);
+        let (eights_a, fours_sum) = csa(fours, fours_a, fours_b);
+        let (twos_a, sum) = csa(sum, word(8), word(9));
+        let (twos_b, sum) = csa(sum, word(10), word(11));
+        let (fours_a, twos_sum) = csa(twos_sum, twos_a, twos_b);
+        let (twos_a, sum) = csa(sum, word(12), word(13));
+        let (twos_b, sum) = csa(sum, word(14), word(15));
+        let (fours_b, twos_sum) = csa(twos_sum, twos_a, twos_b);
+        let (eights_b, fours_sum) = csa(fours_sum, fours_a, fours_b);
+        let (sixteens, eights_sum) = csa(eights, eights_a, eights_b);
+
+        sixteens_total += sixteens.count_ones() as u64;
+        ones = sum;
+        twos = twos_sum;
+        fours = fours_sum;
+        eights = eights_sum;
+    }
+
+    let mut total = 16 * sixteens_total
+        + 8 * eights.count_ones() as u64
+        + 4 * fours.count_ones() as u64
+        + 2 * twos.count_ones() as u64
+        + ones.count_ones() as u64;
+    for x in blocks * 16..len {
+        total += load(x).count_ones() as u64;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::random_words;
+    use alloc::vec::Vec;
+    use rstest::rstest;
+
+    fn naive_count(words: &[u64]) -> u64 {
+        words.iter().map(|word| word.count_ones() as u64).sum()
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(15)]
+    #[case(16)]
+    #[case(63)]
+    #[case(64)]
+    #[case(65)]
+    #[case(1000)]
+    fn test_kernels_against_naive(#[case] len: usize) {
+        let words = random_words(len, len as u64 + 1);
+        let expected = naive_count(&words);
+        assert_eq!(
+            harley_seal(len, |x| words[x]),
+            expected,
+            "Failed for length: {len}"
+        );
+        assert_eq!(
+            count_words_portable(len, |x| words[x]),
+            expected,
+            "Failed for length: {len}"
+        );
+        assert_eq!(
+            count_words_native(len, |x| words[x]),
+            expected,
+            "Failed for length: {len}"
+        );
+        assert_eq!(
+            count_ones_slice(&words),
+            expected,
+            "Failed for length: {len}"
+        );
+    }
+
+    #[test]
+    fn test_harley_seal_saturated_counters() {
+        // Every counter overflows into the next on every block.
+        for len in [16, 17, 160, 1024] {
+            let words = alloc::vec![u64::MAX; len];
+            assert_eq!(harley_seal(len, |x| words[x]), 64 * len as u64);
+        }
+    }
+
+    #[test]
+    fn test_bytes_against_naive() {
+        let words = random_words(200, 99);
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+        let other: Vec<u8> = bytes
+            .iter()
+            .map(|byte| byte.rotate_left(3) ^ 0x5A)
+            .collect();
+        for len in [0, 1, 7, 8, 9, 127, 128, 129, 1000, bytes.len()] {
+            let (bytes, other) = (&bytes[..len], &other[..len]);
+            let expected: u64 = bytes.iter().map(|byte| byte.count_ones() as u64).sum();
+            assert_eq!(
+                count_ones_slice(bytes),
+                expected,
+                "Failed for length: {len}"
+            );
+
+            let expected: u64 = bytes
+                .iter()
+                .zip(other)
+                .map(|(a, b)| (a ^ b).count_ones() as u64)
+                .sum();
+            assert_eq!(
+                hamming_distance(bytes, other),
+                expected,
+                "Failed for length: {len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hamming_distance_words() {
+        let a = random_words(300, 1);
+        let b = random_words(300, 2);
+        let expected: u64 = a
+            .iter()
+            .zip(&b)
+            .map(|(a, b)| (a ^ b).count_ones() as u64)
+            .sum();
+        assert_eq!(hamming_distance(&a, &b), expected);
+        assert_eq!(hamming_distance(&a, &a), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn test_hamming_distance_length_mismatch_panics() {
+        hamming_distance(&[0u64], &[0, 1]);
+    }
+
+    #[test]
+    fn test_count_ones_range_against_naive() {
+        let words = random_words(5, 7);
+        let bit = |x: usize| words[x / 64] >> (x % 64) & 1;
+        for start in 0..=320 {
+            let mut expected = 0;
+            for end in start..=320 {
+                assert_eq!(
+                    count_ones_range(&words, start, end),
+                    expected,
+                    "Failed for range: {start}..{end}"
+                );
+                if end < 320 {
+                    expected += bit(end);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_count_ones_range_out_of_bounds_panics() {
+        count_ones_range(&[0, 0], 0, 129);
+    }
+}
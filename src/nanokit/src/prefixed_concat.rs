@@ -0,0 +1,337 @@
+use crate::varint::{DecodeError, Varint};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Returns the number of bytes `text` takes once written with a LEB128 length prefix.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::prefixed_concat::prefixed_len;
+/// assert_eq!(prefixed_len(""), 1);
+/// assert_eq!(prefixed_len("hello"), 6);
+/// assert_eq!(prefixed_len(&"x".repeat(200)), 202);
+/// ```
+#[inline]
+pub fn prefixed_len(text: &str) -> usize {
+    text.len().encoded_len() + text.len()
+}
+
+/// Serializes two strings as `varint length + bytes` each, into a single exact-size buffer.
+///
+/// The length prefixes are [`Varint`]s (unsigned LEB128); the whole message
+/// is allocated once, then the prefixes and payloads are copied in. Use [`PrefixedReader`]
+/// to read the strings back.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::prefixed_concat::write_prefixed_2;
+/// let message = write_prefixed_2("GET", "/index.html");
+/// assert_eq!(message, b"\x03GET\x0B/index.html");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn write_prefixed_2<S1, S2>(s1: S1, s2: S2) -> Vec<u8>
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+{
+    write_prefixed(&[s1.as_ref(), s2.as_ref()])
+}
+
+/// Serializes three strings as `varint length + bytes` each, into a single exact-size buffer.
+///
+/// See [`write_prefixed_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::prefixed_concat::write_prefixed_3;
+/// assert_eq!(write_prefixed_3("a", "", "bc"), b"\x01a\x00\x02bc");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn write_prefixed_3<S1, S2, S3>(s1: S1, s2: S2, s3: S3) -> Vec<u8>
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+{
+    write_prefixed(&[s1.as_ref(), s2.as_ref(), s3.as_ref()])
+}
+
+/// Serializes four strings as `varint length + bytes` each, into a single exact-size buffer.
+///
+/// See [`write_prefixed_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::prefixed_concat::write_prefixed_4;
+/// assert_eq!(write_prefixed_4("a", "b", "c", "d"), b"\x01a\x01b\x01c\x01d");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn write_prefixed_4<S1, S2, S3, S4>(s1: S1, s2: S2, s3: S3, s4: S4) -> Vec<u8>
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+    S4: AsRef<str>,
+{
+    write_prefixed(&[s1.as_ref(), s2.as_ref(), s3.as_ref(), s4.as_ref()])
+}
+
+/// Serializes five strings as `varint length + bytes` each, into a single exact-size buffer.
+///
+/// See [`write_prefixed_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::prefixed_concat::write_prefixed_5;
+/// assert_eq!(write_prefixed_5("a", "b", "c", "d", "e"), b"\x01a\x01b\x01c\x01d\x01e");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn write_prefixed_5<S1, S2, S3, S4, S5>(s1: S1, s2: S2, s3: S3, s4: S4, s5: S5) -> Vec<u8>
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+    S4: AsRef<str>,
+    S5: AsRef<str>,
+{
+    write_prefixed(&[
+        s1.as_ref(),
+        s2.as_ref(),
+        s3.as_ref(),
+        s4.as_ref(),
+        s5.as_ref(),
+    ])
+}
+
+/// Reads strings written by the `write_prefixed_N` functions, without copying them.
+///
+/// Each call to [`PrefixedReader::read_str`] returns a slice of the original buffer.
+/// The reader also works as an [`Iterator`], which stops at the end of the buffer.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::prefixed_concat::{write_prefixed_3, PrefixedReader};
+/// let message = write_prefixed_3("PUT", "/users/1", "{\"name\":\"Bob\"}");
+/// let mut reader = PrefixedReader::new(&message);
+/// assert_eq!(reader.read_str(), Ok("PUT"));
+/// assert_eq!(reader.read_str(), Ok("/users/1"));
+/// assert_eq!(reader.read_str(), Ok("{\"name\":\"Bob\"}"));
+/// assert!(reader.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrefixedReader<'a> {
+    remaining: &'a [u8],
+}
+
+/// An error returned by [`PrefixedReader::read_str`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadError {
+    /// The buffer ended in the middle of a length prefix or a string.
+    Truncated,
+    /// A length prefix is malformed: it has redundant trailing zero groups, or does not
+    /// fit in a `usize`.
+    InvalidLength,
+    /// A string is not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReadError::Truncated => "buffer ended inside a length-prefixed string",
+            ReadError::InvalidLength => "malformed length prefix",
+            ReadError::InvalidUtf8 => "length-prefixed string is not valid UTF-8",
+        })
+    }
+}
+
+impl core::error::Error for ReadError {}
+
+impl<'a> PrefixedReader<'a> {
+    /// Creates a reader over `buffer`.
+    #[inline]
+    pub const fn new(buffer: &'a [u8]) -> Self {
+        Self { remaining: buffer }
+    }
+
+    /// Returns the bytes that have not been read yet.
+    #[inline]
+    pub const fn remaining(&self) -> &'a [u8] {
+        self.remaining
+    }
+
+    /// Returns `true` if the whole buffer has been read.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    /// Reads the next string, returning a slice of the buffer.
+    ///
+    /// On error, the reader is left unchanged.
+    pub fn read_str(&mut self) -> Result<&'a str, ReadError> {
+        let (len, prefix_len) = usize::decode(self.remaining).map_err(|e| match e {
+            DecodeError::Truncated => ReadError::Truncated,
+            DecodeError::Overlong | DecodeError::Overflow => ReadError::InvalidLength,
+        })?;
+        let payload = self.remaining[prefix_len..]
+            .get(..len)
+            .ok_or(ReadError::Truncated)?;
+        let text = core::str::from_utf8(payload).map_err(|_| ReadError::InvalidUtf8)?;
+
+        self.remaining = &self.remaining[prefix_len + len..];
+        Ok(text)
+    }
+}
+
+impl<'a> Iterator for PrefixedReader<'a> {
+    type Item = Result<&'a str, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let result = self.read_str();
+        if result.is_err() {
+            // Don't keep returning the same error.
+            self.remaining = &[];
+        }
+        Some(result)
+    }
+}
+
+#[inline(always)]
+fn write_prefixed(parts: &[&str]) -> Vec<u8> {
+    let total_length: usize = parts.iter().map(|part| prefixed_len(part)).sum();
+
+    let mut result: Vec<u8> = Vec::with_capacity(total_length);
+    unsafe {
+        let mut dst = result.as_mut_ptr();
+        for part in parts {
+            dst = dst.add(part.len().encode_to_ptr(dst));
+            core::ptr::copy_nonoverlapping(part.as_ptr(), dst, part.len());
+            dst = dst.add(part.len());
+        }
+        result.set_len(total_length);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(1, 1)]
+    #[case(127, 1)]
+    #[case(128, 2)]
+    #[case(16383, 2)]
+    #[case(16384, 3)]
+    #[case(usize::MAX, (usize::BITS as usize).div_ceil(7))]
+    fn test_prefix_len(#[case] len: usize, #[case] expected: usize) {
+        assert_eq!(len.encoded_len(), expected, "Failed for input: {len:?}");
+
+        let mut buffer = [0u8; 16];
+        let written = len.encode_into(&mut buffer).unwrap();
+        assert_eq!(written, expected);
+        assert_eq!(usize::decode(&buffer[..written]), Ok((len, written)));
+
+        // The reader accepts the prefix, but the bytes it announces are missing.
+        let expected = if len == 0 {
+            Ok("")
+        } else {
+            Err(ReadError::Truncated)
+        };
+        assert_eq!(PrefixedReader::new(&buffer[..written]).read_str(), expected);
+    }
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(127, 1)]
+    #[case(128, 2)]
+    #[case(16383, 2)]
+    #[case(16384, 3)]
+    fn test_prefixed_len(#[case] len: usize, #[case] expected_prefix: usize) {
+        let text = "x".repeat(len);
+        assert_eq!(
+            prefixed_len(&text),
+            expected_prefix + len,
+            "Failed for input: {len:?}"
+        );
+        assert_eq!(write_prefixed_2(&text, "").len(), expected_prefix + len + 1);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(127)]
+    #[case(128)]
+    #[case(300)]
+    #[case(20000)]
+    fn test_round_trip_lengths(#[case] len: usize) {
+        let long = "é".repeat(len / 2) + &"x".repeat(len % 2);
+        let message = write_prefixed_3("head", &long, "tail");
+        assert_eq!(message.len(), message.capacity());
+        assert_eq!(
+            message.len(),
+            prefixed_len("head") + prefixed_len(&long) + 5
+        );
+
+        let strings: Result<Vec<_>, _> = PrefixedReader::new(&message).collect();
+        assert_eq!(strings, Ok(vec!["head", long.as_str(), "tail"]));
+    }
+
+    #[test]
+    fn test_write_prefixed_n() {
+        let name = String::from("Bob");
+        assert_eq!(write_prefixed_2(&name, ""), b"\x03Bob\x00");
+        assert_eq!(write_prefixed_4("", "", "", ""), b"\x00\x00\x00\x00");
+        assert_eq!(
+            write_prefixed_5(name, "ü", "b", "c", "d"),
+            b"\x03Bob\x02\xc3\xbc\x01b\x01c\x01d"
+        );
+    }
+
+    #[rstest]
+    #[case(b"\x05abc", ReadError::Truncated)]
+    #[case(b"\x80", ReadError::Truncated)]
+    #[case(b"\x81\x00", ReadError::InvalidLength)]
+    #[case(
+        b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01",
+        ReadError::InvalidLength
+    )]
+    #[case(b"\x02\xff\xfe", ReadError::InvalidUtf8)]
+    fn test_read_errors(#[case] input: &[u8], #[case] expected: ReadError) {
+        let mut reader = PrefixedReader::new(input);
+        assert_eq!(
+            reader.read_str(),
+            Err(expected),
+            "Failed for input: {input:?}"
+        );
+        assert_eq!(reader.remaining(), input);
+
+        assert_eq!(reader.next(), Some(Err(expected)));
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn test_read_is_zero_copy() {
+        let message = write_prefixed_2("abc", "def");
+        let mut reader = PrefixedReader::new(&message);
+        let first = reader.read_str().unwrap();
+        assert_eq!(first.as_ptr(), message[1..].as_ptr());
+        assert_eq!(reader.remaining(), b"\x03def");
+    }
+}
@@ -0,0 +1,366 @@
+use crate::bitset::BitVec;
+use crate::popcount::count_ones_slice;
+use alloc::vec::Vec;
+
+/// Bits covered by each absolute count in the rank directory.
+const SUPERBLOCK_BITS: usize = 1 << 16;
+/// Bits covered by each count relative to its superblock.
+const BLOCK_BITS: usize = 512;
+const WORDS_PER_BLOCK: usize = BLOCK_BITS / 64;
+const BLOCKS_PER_SUPERBLOCK: usize = SUPERBLOCK_BITS / BLOCK_BITS;
+/// Every `SELECT_SAMPLE`th set bit records the block it falls in.
+const SELECT_SAMPLE: usize = 8192;
+
+/// An immutable bit vector with constant-time rank and fast select queries.
+///
+/// Rank uses a two-level directory: a `u64` count of the set bits before every 65536-bit
+/// superblock, and a `u16` count relative to the superblock before every 512-bit block.
+/// A query adds both counts to the popcount of at most 8 words. The directory takes about
+/// 3.2% of the size of the bits.
+///
+/// Select samples the block of every 8192nd set bit, binary searches the blocks between two
+/// samples, then finds the bit within its word with a broadword select.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::rank_select::RankSelect;
+/// let bits = RankSelect::from_words(&[0b1011_0010], 8);
+/// assert_eq!(bits.rank1(5), 2);
+/// assert_eq!(bits.rank0(5), 3);
+/// assert_eq!(bits.select1(0), Some(1));
+/// assert_eq!(bits.select1(3), Some(7));
+/// assert_eq!(bits.select1(4), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankSelect {
+    /// Bits at or above `len` in the last word are always cleared.
+    words: Vec<u64>,
+    len: usize,
+    ones: usize,
+    /// Set bits before each superblock, including one for the superblock starting at `len`.
+    superblocks: Vec<u64>,
+    /// Set bits between the start of the superblock and each block, including one for the
+    /// block starting at `len`.
+    blocks: Vec<u16>,
+    /// The block containing each `SELECT_SAMPLE`th set bit.
+    select_samples: Vec<usize>,
+}
+
+impl RankSelect {
+    /// Builds the directory over the first `len` bits of `words`; bit `i` is bit `i % 64` of
+    /// word `i / 64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `words` holds fewer than `len` bits.
+    pub fn from_words(words: &[u64], len: usize) -> Self {
+        Self::from(BitVec::from_words(words, len))
+    }
+
+    fn build(words: Vec<u64>, len: usize) -> Self {
+        let block_count = len / BLOCK_BITS + 1;
+        let mut superblocks = Vec::with_capacity(len / SUPERBLOCK_BITS + 1);
+        let mut blocks = Vec::with_capacity(block_count);
+        let mut select_samples = Vec::new();
+
+        let mut total = 0usize;
+        let mut superblock_start = 0usize;
+        for block in 0..block_count {
+            if block % BLOCKS_PER_SUPERBLOCK == 0 {
+                superblocks.push(total as u64);
+                superblock_start = total;
+            }
+            blocks.push((total - superblock_start) as u16);
+
+            let start = (block * WORDS_PER_BLOCK).min(words.len());
+            let end = (start + WORDS_PER_BLOCK).min(words.len());
+            let ones = count_ones_slice(&words[start..end]) as usize;
+            while select_samples.len() * SELECT_SAMPLE < total + ones {
+                select_samples.push(block);
+            }
+            total += ones;
+        }
+
+        Self {
+            words,
+            len,
+            ones: total,
+            superblocks,
+            blocks,
+            select_samples,
+        }
+    }
+
+    /// Returns the number of bits.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no bits.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of set bits.
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Returns the words backing the vector. Bits past [`RankSelect::len`] are cleared.
+    #[inline]
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the bit at `index`, or [`None`] if it is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.words[index / 64] >> (index % 64) & 1 != 0)
+    }
+
+    /// Returns the number of set bits before `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than [`RankSelect::len`].
+    #[inline]
+    pub fn rank1(&self, index: usize) -> usize {
+        assert!(
+            index <= self.len,
+            "rank index {index} out of bounds for length {}",
+            self.len
+        );
+
+        let block = index / BLOCK_BITS;
+        let word = index / 64;
+        let mut rank = self.block_rank(block)
+            + count_ones_slice(&self.words[block * WORDS_PER_BLOCK..word]) as usize;
+        if !index.is_multiple_of(64) {
+            rank += (self.words[word] << (64 - index % 64)).count_ones() as usize;
+        }
+        rank
+    }
+
+    /// Returns the number of cleared bits before `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than [`RankSelect::len`].
+    #[inline]
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Returns the position of the set bit with rank `rank` (counting from 0), or [`None`] if
+    /// there are not that many set bits.
+    pub fn select1(&self, rank: usize) -> Option<usize> {
+        if rank >= self.ones {
+            return None;
+        }
+
+        // The bit lies between the blocks of the samples before and after it.
+        let sample = rank / SELECT_SAMPLE;
+        let mut low = self.select_samples[sample];
+        let mut high = self
+            .select_samples
+            .get(sample + 1)
+            .copied()
+            .unwrap_or(self.blocks.len() - 1);
+
+        // Find the last block that starts with at most `rank` set bits before it.
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if self.block_rank(mid) <= rank {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        let mut remaining = rank - self.block_rank(low);
+        for word in low * WORDS_PER_BLOCK.. {
+            let bits = self.words[word];
+            let ones = bits.count_ones() as usize;
+            if remaining < ones {
+                return Some(word * 64 + select_in_word(bits, remaining as u32) as usize);
+            }
+            remaining -= ones;
+        }
+        unreachable!("rank is less than the number of set bits")
+    }
+
+    /// Returns the number of set bits before `block`.
+    #[inline(always)]
+    fn block_rank(&self, block: usize) -> usize {
+        self.superblocks[block / BLOCKS_PER_SUPERBLOCK] as usize + self.blocks[block] as usize
+    }
+}
+
+impl From<BitVec> for RankSelect {
+    fn from(bits: BitVec) -> Self {
+        let len = bits.len();
+        Self::build(bits.into_words(), len)
+    }
+}
+
+/// `SELECT_IN_BYTE[rank << 8 | byte]` is the position of the set bit with rank `rank` in
+/// `byte`, or 8 if there are not that many.
+const SELECT_IN_BYTE: [u8; 2048] = {
+    let mut table = [8u8; 2048];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut rank = 0;
+        let mut bit = 0;
+        while bit < 8 {
+            if byte >> bit & 1 != 0 {
+                table[rank << 8 | byte] = bit as u8;
+                rank += 1;
+            }
+            bit += 1;
+        }
+        byte += 1;
+    }
+    table
+};
+
+/// Returns the position of the set bit with rank `rank` in `word`, which must have more
+/// than `rank` set bits.
+///
+/// This is Vigna's broadword select: it computes the running popcount of every byte at once,
+/// finds the byte holding the bit with a parallel comparison, and finishes with a table.
+#[inline]
+fn select_in_word(word: u64, rank: u32) -> u32 {
+    const ONES: u64 = 0x0101_0101_0101_0101;
+    const HIGHS: u64 = 0x8080_8080_8080_8080;
+
+    // Popcount of each byte, then a running total across the bytes.
+    let mut sums = word - ((word & 0xAAAA_AAAA_AAAA_AAAA) >> 1);
+    sums = (sums & 0x3333_3333_3333_3333) + ((sums >> 2) & 0x3333_3333_3333_3333);
+    sums = (sums + (sums >> 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    sums = sums.wrapping_mul(ONES);
+
+    // Count the bytes whose running total is at most `rank`; that count is the byte to look
+    // in. All totals are at most 64, so no byte borrows from its neighbour.
+    let ranks = rank as u64 * ONES;
+    let at_most = ((((ranks | HIGHS) - (sums & !HIGHS)) ^ sums ^ ranks) & HIGHS) >> 7;
+    let place = (at_most.wrapping_mul(ONES) >> 53) & !7;
+
+    let byte = (word >> place) & 0xFF;
+    let before = ((sums << 8) >> place) & 0xFF;
+    place as u32 + SELECT_IN_BYTE[((rank as u64 - before) << 8 | byte) as usize] as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{next_random, random_bits};
+    use rstest::rstest;
+
+    #[test]
+    fn test_select_in_word() {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        let words = [1, 1 << 63, u64::MAX, 0x8000_0000_0000_0001, 0xFF00];
+        for word in words
+            .into_iter()
+            .chain((0..1000).map(|_| next_random(&mut state)))
+        {
+            let mut rest = word;
+            for rank in 0..word.count_ones() {
+                assert_eq!(
+                    select_in_word(word, rank),
+                    rest.trailing_zeros(),
+                    "Failed for input: {word:#x}, rank {rank}"
+                );
+                rest &= rest - 1;
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(1, 1)]
+    #[case(64, 2)]
+    #[case(513, 3)]
+    #[case(10_000, 1)]
+    #[case(10_000, 1000)]
+    #[case(3 * SUPERBLOCK_BITS + 100, 2)]
+    #[case(2 * SUPERBLOCK_BITS, 9)]
+    #[case(SUPERBLOCK_BITS + 7, 20_000)]
+    fn test_rank_select_against_naive(#[case] len: usize, #[case] sparsity: u64) {
+        let words = random_bits(len, sparsity, len as u64 * 31 + sparsity);
+        let bits = RankSelect::from_words(&words, len);
+        assert_eq!(bits.len(), len);
+
+        let mut positions = Vec::new();
+        for x in 0..len {
+            assert_eq!(bits.rank1(x), positions.len(), "Failed for index: {x}");
+            let set = words[x / 64] >> (x % 64) & 1 != 0;
+            assert_eq!(bits.get(x), Some(set));
+            if set {
+                positions.push(x);
+            }
+        }
+        assert_eq!(bits.rank1(len), positions.len());
+        assert_eq!(bits.rank0(len), len - positions.len());
+        assert_eq!(bits.count_ones(), positions.len());
+        assert_eq!(bits.get(len), None);
+
+        for (rank, &position) in positions.iter().enumerate() {
+            assert_eq!(
+                bits.select1(rank),
+                Some(position),
+                "Failed for rank: {rank}"
+            );
+        }
+        assert_eq!(bits.select1(positions.len()), None);
+    }
+
+    #[test]
+    fn test_all_ones_and_all_zeros() {
+        let len = SUPERBLOCK_BITS + 1000;
+        let ones = RankSelect::from_words(&alloc::vec![u64::MAX; len.div_ceil(64)], len);
+        assert_eq!(ones.count_ones(), len);
+        assert_eq!(ones.rank1(len), len);
+        assert_eq!(ones.select1(len - 1), Some(len - 1));
+        assert_eq!(ones.select1(SUPERBLOCK_BITS), Some(SUPERBLOCK_BITS));
+        assert_eq!(ones.select1(len), None);
+
+        let zeros = RankSelect::from(BitVec::with_len(len));
+        assert_eq!(zeros.rank1(len), 0);
+        assert_eq!(zeros.rank0(len), len);
+        assert_eq!(zeros.select1(0), None);
+    }
+
+    #[test]
+    fn test_from_bitvec_matches_from_words() {
+        let words = random_bits(5000, 3, 7);
+        let bits = BitVec::from_words(&words, 4999);
+        let from_bits = RankSelect::from(bits.clone());
+        assert_eq!(from_bits, RankSelect::from_words(&words, 4999));
+        assert_eq!(from_bits.as_words(), bits.as_words());
+    }
+
+    #[test]
+    fn test_directory_overhead() {
+        let len = 1 << 22;
+        let bits = RankSelect::from_words(&random_bits(len, 2, 11), len);
+        let directory_bits = bits.superblocks.len() * 64
+            + bits.blocks.len() * 16
+            + bits.select_samples.len() * usize::BITS as usize;
+        let overhead = directory_bits as f64 / len as f64;
+        assert!(overhead < 0.04, "Overhead too high: {overhead}");
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_rank_out_of_bounds_panics() {
+        RankSelect::from_words(&[0], 10).rank1(11);
+    }
+}
@@ -0,0 +1,839 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Bound, Range, RangeBounds};
+
+/// Maximum size of a single leaf, in bytes.
+///
+/// Neighbouring leaves are merged while their combined size fits, so repeated small
+/// inserts don't leave the tree full of tiny leaves.
+const MAX_LEAF: usize = 1024;
+
+/// A string stored as a balanced tree of chunks.
+///
+/// Inserting or deleting in the middle of a large text only touches the leaves around the
+/// edit and the path to them, rather than copying the whole text as `concat_3(before,
+/// insert, after)` would. Positions can be given in bytes or in chars; byte positions must
+/// lie on char boundaries, as with [`String`].
+///
+/// Use [`Rope::flatten`] to turn it back into a `String` with a single allocation.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::rope::Rope;
+/// let mut rope = Rope::from("Hello world!");
+/// rope.insert(5, ",");
+/// rope.delete(7..13);
+/// rope.append("there!");
+/// assert_eq!(rope.flatten(), "Hello, there!");
+///
+/// // Char indices count characters, not bytes.
+/// let mut rope = Rope::from("größe");
+/// rope.insert_at_char(4, "ss");
+/// assert_eq!(rope.flatten(), "größsse");
+/// ```
+#[derive(Clone, Default)]
+pub struct Rope {
+    root: Option<Box<Node>>,
+}
+
+#[derive(Clone)]
+enum Node {
+    Leaf {
+        text: String,
+        chars: usize,
+    },
+    Branch {
+        left: Box<Node>,
+        right: Box<Node>,
+        len: usize,
+        chars: usize,
+        height: u8,
+    },
+}
+
+impl Node {
+    fn leaf(text: String) -> Box<Self> {
+        let chars = text.chars().count();
+        Box::new(Node::Leaf { text, chars })
+    }
+
+    fn branch(left: Box<Self>, right: Box<Self>) -> Box<Self> {
+        Box::new(Node::Branch {
+            len: left.len() + right.len(),
+            chars: left.chars() + right.chars(),
+            height: left.height().max(right.height()) + 1,
+            left,
+            right,
+        })
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf { text, .. } => text.len(),
+            Node::Branch { len, .. } => *len,
+        }
+    }
+
+    #[inline]
+    fn chars(&self) -> usize {
+        match self {
+            Node::Leaf { chars, .. } | Node::Branch { chars, .. } => *chars,
+        }
+    }
+
+    #[inline]
+    fn height(&self) -> u8 {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Branch { height, .. } => *height,
+        }
+    }
+}
+
+impl Rope {
+    /// Creates an empty rope.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns the length of the text, in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.len())
+    }
+
+    /// Returns the length of the text, in chars.
+    #[inline]
+    pub fn len_chars(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.chars())
+    }
+
+    /// Returns `true` if the rope contains no text.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns `true` if `byte_idx` lies on a char boundary, or at the end of the text.
+    pub fn is_char_boundary(&self, byte_idx: usize) -> bool {
+        let Some(mut node) = self.root.as_deref() else {
+            return byte_idx == 0;
+        };
+
+        let mut idx = byte_idx;
+        loop {
+            match node {
+                Node::Leaf { text, .. } => return text.is_char_boundary(idx),
+                Node::Branch { left, right, .. } => {
+                    if idx <= left.len() {
+                        node = left;
+                    } else {
+                        idx -= left.len();
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts a char index into a byte index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is greater than [`Rope::len_chars`].
+    pub fn char_to_byte(&self, char_idx: usize) -> usize {
+        assert!(
+            char_idx <= self.len_chars(),
+            "char index {char_idx} out of bounds"
+        );
+
+        let mut node = match self.root.as_deref() {
+            Some(node) => node,
+            None => return 0,
+        };
+
+        let mut chars = char_idx;
+        let mut bytes = 0;
+        loop {
+            match node {
+                Node::Leaf { text, .. } => {
+                    return bytes + text.char_indices().nth(chars).map_or(text.len(), |x| x.0)
+                }
+                Node::Branch { left, right, .. } => {
+                    if chars < left.chars() {
+                        node = left;
+                    } else {
+                        chars -= left.chars();
+                        bytes += left.len();
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts a byte index into a char index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_idx` is out of bounds or not on a char boundary.
+    pub fn byte_to_char(&self, byte_idx: usize) -> usize {
+        assert_boundary(self, byte_idx);
+
+        let mut node = match self.root.as_deref() {
+            Some(node) => node,
+            None => return 0,
+        };
+
+        let mut bytes = byte_idx;
+        let mut chars = 0;
+        loop {
+            match node {
+                Node::Leaf { text, .. } => return chars + text[..bytes].chars().count(),
+                Node::Branch { left, right, .. } => {
+                    if bytes < left.len() {
+                        node = left;
+                    } else {
+                        bytes -= left.len();
+                        chars += left.chars();
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Appends `text` to the end of the rope.
+    pub fn append(&mut self, text: &str) {
+        let right = build(text);
+        self.root = join_opt(self.root.take(), right);
+    }
+
+    /// Appends another rope to the end of this one, without copying its text.
+    pub fn append_rope(&mut self, other: Rope) {
+        self.root = join_opt(self.root.take(), other.root);
+    }
+
+    /// Inserts `text` at byte index `byte_idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_idx` is out of bounds or not on a char boundary.
+    pub fn insert(&mut self, byte_idx: usize, text: &str) {
+        assert_boundary(self, byte_idx);
+        if text.is_empty() {
+            return;
+        }
+
+        let (left, right) = split_opt(self.root.take(), byte_idx);
+        self.root = join_opt(join_opt(left, build(text)), right);
+    }
+
+    /// Inserts `text` at char index `char_idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is greater than [`Rope::len_chars`].
+    pub fn insert_at_char(&mut self, char_idx: usize, text: &str) {
+        self.insert(self.char_to_byte(char_idx), text);
+    }
+
+    /// Removes the given byte range from the rope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or either end is not on a char boundary.
+    pub fn delete(&mut self, range: impl RangeBounds<usize>) {
+        let range = resolve_range(range, self.len());
+        assert_boundary(self, range.start);
+        assert_boundary(self, range.end);
+        if range.is_empty() {
+            return;
+        }
+
+        let (rest, right) = split_opt(self.root.take(), range.end);
+        let (left, _) = split_opt(rest, range.start);
+        self.root = join_opt(left, right);
+    }
+
+    /// Removes the given char range from the rope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn delete_chars(&mut self, range: impl RangeBounds<usize>) {
+        let range = resolve_range(range, self.len_chars());
+        self.delete(self.char_to_byte(range.start)..self.char_to_byte(range.end));
+    }
+
+    /// Splits the rope in two at byte index `byte_idx`, returning the part after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_idx` is out of bounds or not on a char boundary.
+    pub fn split_off(&mut self, byte_idx: usize) -> Rope {
+        assert_boundary(self, byte_idx);
+        let (left, right) = split_opt(self.root.take(), byte_idx);
+        self.root = left;
+        Rope { root: right }
+    }
+
+    /// Copies the given byte range into a new [`String`], with a single allocation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or either end is not on a char boundary.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> String {
+        let range = resolve_range(range, self.len());
+        assert_boundary(self, range.start);
+        assert_boundary(self, range.end);
+
+        let mut result = String::with_capacity(range.len());
+        let mut offset = 0;
+        for chunk in self.chunks() {
+            let chunk_end = offset + chunk.len();
+            if chunk_end > range.start && offset < range.end {
+                let start = range.start.saturating_sub(offset);
+                let end = (range.end - offset).min(chunk.len());
+                result.push_str(&chunk[start..end]);
+            }
+
+            if chunk_end >= range.end {
+                break;
+            }
+            offset = chunk_end;
+        }
+
+        result
+    }
+
+    /// Copies the given char range into a new [`String`], with a single allocation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn slice_chars(&self, range: impl RangeBounds<usize>) -> String {
+        let range = resolve_range(range, self.len_chars());
+        self.slice(self.char_to_byte(range.start)..self.char_to_byte(range.end))
+    }
+
+    /// Copies the whole text into a new [`String`], with a single allocation.
+    pub fn flatten(&self) -> String {
+        let mut result = String::with_capacity(self.len());
+        for chunk in self.chunks() {
+            result.push_str(chunk);
+        }
+        result
+    }
+
+    /// Returns an iterator over the chunks of text stored in the rope, in order.
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+}
+
+/// An iterator over the chunks of a [`Rope`], created by [`Rope::chunks`].
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            match self.stack.pop()? {
+                Node::Leaf { text, .. } => return Some(text),
+                Node::Branch { left, right, .. } => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+    }
+}
+
+/// Builds a balanced tree of leaves from `text`.
+fn build(text: &str) -> Option<Box<Node>> {
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut leaves = Vec::with_capacity(text.len().div_ceil(MAX_LEAF));
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut end = MAX_LEAF.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        leaves.push(Some(Node::leaf(String::from(&rest[..end]))));
+        rest = &rest[end..];
+    }
+
+    Some(build_balanced(&mut leaves))
+}
+
+fn build_balanced(leaves: &mut [Option<Box<Node>>]) -> Box<Node> {
+    if let [leaf] = leaves {
+        return leaf.take().unwrap();
+    }
+
+    let (left, right) = leaves.split_at_mut(leaves.len() / 2);
+    Node::branch(build_balanced(left), build_balanced(right))
+}
+
+fn join_opt(left: Option<Box<Node>>, right: Option<Box<Node>>) -> Option<Box<Node>> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(join(left, right)),
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+/// Concatenates two trees, keeping the result balanced.
+fn join(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    let (left_height, right_height) = (left.height(), right.height());
+
+    // Descend into the taller tree until the heights match. A leaf is always carried down
+    // to the edge of the other tree, so it can be merged with its neighbour.
+    if left_height > right_height && (left_height > right_height + 1 || right_height == 0) {
+        let Node::Branch {
+            left: outer,
+            right: inner,
+            ..
+        } = *left
+        else {
+            unreachable!()
+        };
+        return balance(outer, join(inner, right));
+    }
+
+    if right_height > left_height && (right_height > left_height + 1 || left_height == 0) {
+        let Node::Branch {
+            left: inner,
+            right: outer,
+            ..
+        } = *right
+        else {
+            unreachable!()
+        };
+        return balance(join(left, inner), outer);
+    }
+
+    match (*left, *right) {
+        (
+            Node::Leaf {
+                text: mut left_text,
+                chars: left_chars,
+            },
+            Node::Leaf {
+                text: right_text,
+                chars: right_chars,
+            },
+        ) if left_text.len() + right_text.len() <= MAX_LEAF => {
+            left_text.push_str(&right_text);
+            Box::new(Node::Leaf {
+                text: left_text,
+                chars: left_chars + right_chars,
+            })
+        }
+        (left, right) => Node::branch(Box::new(left), Box::new(right)),
+    }
+}
+
+/// Creates a branch from two balanced trees whose heights differ by at most 2, rotating
+/// once or twice if needed.
+fn balance(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    if left.height() > right.height() + 1 {
+        let Node::Branch {
+            left: ll,
+            right: lr,
+            ..
+        } = *left
+        else {
+            unreachable!()
+        };
+
+        if ll.height() >= lr.height() {
+            return Node::branch(ll, Node::branch(lr, right));
+        }
+
+        let Node::Branch {
+            left: lrl,
+            right: lrr,
+            ..
+        } = *lr
+        else {
+            unreachable!()
+        };
+        return Node::branch(Node::branch(ll, lrl), Node::branch(lrr, right));
+    }
+
+    if right.height() > left.height() + 1 {
+        let Node::Branch {
+            left: rl,
+            right: rr,
+            ..
+        } = *right
+        else {
+            unreachable!()
+        };
+
+        if rr.height() >= rl.height() {
+            return Node::branch(Node::branch(left, rl), rr);
+        }
+
+        let Node::Branch {
+            left: rll,
+            right: rlr,
+            ..
+        } = *rl
+        else {
+            unreachable!()
+        };
+        return Node::branch(Node::branch(left, rll), Node::branch(rlr, rr));
+    }
+
+    Node::branch(left, right)
+}
+
+fn split_opt(node: Option<Box<Node>>, byte_idx: usize) -> (Option<Box<Node>>, Option<Box<Node>>) {
+    match node {
+        Some(node) => split(node, byte_idx),
+        None => (None, None),
+    }
+}
+
+/// Splits a tree at `byte_idx`, which must be on a char boundary.
+fn split(node: Box<Node>, byte_idx: usize) -> (Option<Box<Node>>, Option<Box<Node>>) {
+    if byte_idx == 0 {
+        return (None, Some(node));
+    }
+    if byte_idx >= node.len() {
+        return (Some(node), None);
+    }
+
+    match *node {
+        Node::Leaf { mut text, .. } => {
+            let right = text.split_off(byte_idx);
+            (Some(Node::leaf(text)), Some(Node::leaf(right)))
+        }
+        Node::Branch { left, right, .. } => {
+            let left_len = left.len();
+            if byte_idx <= left_len {
+                let (a, b) = split(left, byte_idx);
+                (a, join_opt(b, Some(right)))
+            } else {
+                let (a, b) = split(right, byte_idx - left_len);
+                (join_opt(Some(left), a), b)
+            }
+        }
+    }
+}
+
+#[track_caller]
+fn assert_boundary(rope: &Rope, byte_idx: usize) {
+    assert!(
+        byte_idx <= rope.len() && rope.is_char_boundary(byte_idx),
+        "byte index {byte_idx} is out of bounds or not a char boundary"
+    );
+}
+
+#[track_caller]
+fn resolve_range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(start <= end, "range start {start} is after end {end}");
+    assert!(end <= len, "range end {end} is out of bounds");
+    start..end
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        Self { root: build(text) }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Self {
+        if text.len() <= MAX_LEAF {
+            let root = (!text.is_empty()).then(|| Node::leaf(text));
+            return Self { root };
+        }
+
+        Self::from(text.as_str())
+    }
+}
+
+impl From<&Rope> for String {
+    fn from(rope: &Rope) -> Self {
+        rope.flatten()
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .chunks()
+                .flat_map(str::bytes)
+                .eq(other.chunks().flat_map(str::bytes))
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        let mut offset = 0;
+        self.chunks().all(|chunk| {
+            let end = offset + chunk.len();
+            let matches = &other.as_bytes()[offset..end] == chunk.as_bytes();
+            offset = end;
+            matches
+        })
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Write::write_char(f, '"')?;
+        for chunk in self.chunks() {
+            fmt::Display::fmt(&chunk.escape_debug(), f)?;
+        }
+        fmt::Write::write_char(f, '"')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::next_random;
+    use alloc::format;
+    use rstest::rstest;
+
+    /// Checks the cached lengths, balance and leaf sizes of every node.
+    fn check_invariants(rope: &Rope) {
+        fn check(node: &Node) -> (usize, usize, u8) {
+            match node {
+                Node::Leaf { text, chars } => {
+                    assert!(!text.is_empty() && text.len() <= MAX_LEAF);
+                    assert_eq!(*chars, text.chars().count());
+                    (text.len(), *chars, 0)
+                }
+                Node::Branch {
+                    left,
+                    right,
+                    len,
+                    chars,
+                    height,
+                } => {
+                    let (left_len, left_chars, left_height) = check(left);
+                    let (right_len, right_chars, right_height) = check(right);
+                    assert!(left_height.abs_diff(right_height) <= 1);
+                    assert_eq!(*len, left_len + right_len);
+                    assert_eq!(*chars, left_chars + right_chars);
+                    assert_eq!(*height, left_height.max(right_height) + 1);
+                    (*len, *chars, *height)
+                }
+            }
+        }
+
+        if let Some(root) = rope.root.as_deref() {
+            check(root);
+        }
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("a")]
+    #[case("héllo wörld")]
+    fn test_from_and_flatten(#[case] input: &str) {
+        let rope = Rope::from(input);
+        check_invariants(&rope);
+        assert_eq!(rope.len(), input.len());
+        assert_eq!(rope.len_chars(), input.chars().count());
+        assert_eq!(rope.is_empty(), input.is_empty());
+        assert_eq!(rope, input);
+
+        let result = rope.flatten();
+        assert_eq!(result, input, "Failed for input: {input:?}");
+        assert_eq!(result.capacity(), input.len());
+    }
+
+    #[test]
+    fn test_large_text_is_chunked() {
+        let text = "ü".repeat(MAX_LEAF * 3);
+        let rope = Rope::from(text.as_str());
+        check_invariants(&rope);
+        assert!(rope.chunks().count() > 1);
+        assert!(rope.chunks().all(|chunk| chunk.len() <= MAX_LEAF));
+        assert_eq!(rope.flatten(), text);
+        assert_eq!(Rope::from(text.clone()), rope);
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(1, 1)]
+    #[case(2, 3)]
+    #[case(3, 6)]
+    #[case(4, 10)]
+    #[case(5, 11)]
+    fn test_char_byte_conversion(#[case] char_idx: usize, #[case] byte_idx: usize) {
+        let rope = Rope::from("aé€𝄞b");
+        assert_eq!(rope.char_to_byte(char_idx), byte_idx);
+        assert_eq!(rope.byte_to_char(byte_idx), char_idx);
+    }
+
+    #[test]
+    fn test_char_indices_across_leaves() {
+        let text = "aé€𝄞".repeat(MAX_LEAF);
+        let rope = Rope::from(text.as_str());
+        for (char_idx, (byte_idx, _)) in text.char_indices().enumerate().step_by(97) {
+            assert_eq!(rope.char_to_byte(char_idx), byte_idx);
+            assert_eq!(rope.byte_to_char(byte_idx), char_idx);
+        }
+        assert_eq!(rope.char_to_byte(rope.len_chars()), text.len());
+    }
+
+    #[test]
+    fn test_insert_delete_slice_by_char() {
+        let mut rope = Rope::from("größe");
+        rope.insert_at_char(3, "ß");
+        assert_eq!(rope, "größße");
+        rope.delete_chars(1..3);
+        assert_eq!(rope, "gßße");
+        assert_eq!(rope.slice_chars(1..=2), "ßß");
+        assert_eq!(rope.slice(1..5), "ßß");
+        assert_eq!(rope.slice(..), "gßße");
+    }
+
+    #[test]
+    fn test_split_off_and_append_rope() {
+        let mut rope = Rope::from("Hello, world!");
+        let tail = rope.split_off(5);
+        assert_eq!(rope, "Hello");
+        assert_eq!(tail, ", world!");
+
+        rope.append_rope(tail);
+        check_invariants(&rope);
+        assert_eq!(rope, "Hello, world!");
+    }
+
+    #[test]
+    fn test_repeated_appends_merge_leaves() {
+        let mut rope = Rope::new();
+        for _ in 0..MAX_LEAF * 4 {
+            rope.append("x");
+        }
+        check_invariants(&rope);
+        assert_eq!(rope.len(), MAX_LEAF * 4);
+        assert!(rope.chunks().count() <= 8);
+    }
+
+    #[test]
+    fn test_random_edits_match_string() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        let mut expected = String::new();
+        let mut rope = Rope::new();
+        let pieces = [
+            "",
+            "a",
+            "ü",
+            "€€",
+            "hello ",
+            "𝄞\n",
+            &"long piece ".repeat(200),
+        ];
+
+        for _ in 0..2000 {
+            let op = next_random(&mut state) % 4;
+            let chars = expected.chars().count();
+            if op < 2 || chars == 0 {
+                let piece = pieces[next_random(&mut state) as usize % pieces.len()];
+                let char_idx = next_random(&mut state) as usize % (chars + 1);
+                let byte_idx = rope.char_to_byte(char_idx);
+                expected.insert_str(byte_idx, piece);
+                rope.insert(byte_idx, piece);
+            } else if op == 2 {
+                let start = next_random(&mut state) as usize % chars;
+                let end = start + next_random(&mut state) as usize % (chars - start).min(300);
+                let byte_start = expected.char_indices().nth(start).unwrap().0;
+                let byte_end = expected
+                    .char_indices()
+                    .nth(end)
+                    .map_or(expected.len(), |x| x.0);
+                expected.replace_range(byte_start..byte_end, "");
+                rope.delete_chars(start..end);
+            } else {
+                let start = next_random(&mut state) as usize % chars;
+                let end = start + next_random(&mut state) as usize % (chars - start);
+                let byte_start = rope.char_to_byte(start);
+                let byte_end = rope.char_to_byte(end);
+                assert_eq!(
+                    rope.slice(byte_start..byte_end),
+                    expected[byte_start..byte_end]
+                );
+            }
+
+            assert_eq!(rope.len(), expected.len());
+            assert_eq!(rope.len_chars(), expected.chars().count());
+        }
+
+        check_invariants(&rope);
+        assert_eq!(rope.flatten(), expected);
+    }
+
+    #[test]
+    fn test_formatting() {
+        let rope = Rope::from("a\"b\n");
+        assert_eq!(format!("{rope}"), "a\"b\n");
+        assert_eq!(format!("{rope:?}"), "\"a\\\"b\\n\"");
+        assert_eq!(String::from(&rope), "a\"b\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn test_insert_inside_char_panics() {
+        Rope::from("é").insert(1, "x");
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_delete_out_of_bounds_panics() {
+        Rope::from("abc").delete(1..4);
+    }
+}
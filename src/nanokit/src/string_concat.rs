@@ -1,10 +1,104 @@
+use crate::string_escape::{self, Escape};
 use alloc::string::String;
+use core::{fmt, mem::MaybeUninit, ops::Deref};
+
+/// A value that can be passed to the `concat_N` functions.
+///
+/// This is implemented for every type that implements [`AsRef<str>`], and for wrappers
+/// that transform their text while it is copied, such as [`JsonEscaped`].
+/// The final length of every part is computed before the result is allocated,
+/// so transforming wrappers don't need any temporary strings.
+///
+/// To pass your own type, return a [`Part`] describing its text:
+///
+/// ```
+/// use nanokit::string_concat::{concat_2, ConcatPart, Part};
+///
+/// struct UserName {
+///     name: String,
+/// }
+///
+/// impl ConcatPart for UserName {
+///     fn to_part(&self) -> Part<'_> {
+///         Part::new(&self.name)
+///     }
+/// }
+///
+/// let user = UserName { name: String::from("Bob") };
+/// assert_eq!(concat_2("name: ", user), "name: Bob");
+/// ```
+///
+/// [`JsonEscaped`]: crate::string_escape::JsonEscaped
+pub trait ConcatPart {
+    /// Returns the text of this part, and how it should be written.
+    fn to_part(&self) -> Part<'_>;
+}
+
+impl<T: AsRef<str> + ?Sized> ConcatPart for T {
+    #[inline(always)]
+    fn to_part(&self) -> Part<'_> {
+        Part::new(self.as_ref())
+    }
+}
+
+/// A piece of text, and how it is written when concatenated.
+///
+/// Obtained from [`ConcatPart::to_part`].
+#[derive(Debug, Clone, Copy)]
+pub struct Part<'a> {
+    text: &'a str,
+    len: usize,
+    escape: Escape,
+}
+
+impl<'a> Part<'a> {
+    /// Creates a part which is copied verbatim.
+    #[inline(always)]
+    pub const fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            len: text.len(),
+            escape: Escape::None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn escaped(text: &'a str, escape: Escape) -> Self {
+        Self {
+            text,
+            len: string_escape::escaped_len(text, escape),
+            escape,
+        }
+    }
+
+    /// Returns the number of bytes this part occupies once written.
+    #[inline(always)]
+    #[allow(clippy::len_without_is_empty)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Writes this part to `dst`.
+    ///
+    /// # Safety
+    ///
+    /// `dst` must be valid for writes of [`len`](Part::len) bytes.
+    #[inline(always)]
+    pub(crate) unsafe fn write(&self, dst: *mut u8) {
+        match self.escape {
+            Escape::None => {
+                core::ptr::copy_nonoverlapping(self.text.as_ptr(), dst, self.text.len())
+            }
+            escape => string_escape::write_escaped(dst, self.text, escape),
+        }
+    }
+}
 
 /// Concatenates two strings using unsafe Rust for better performance.
 ///
 /// This function takes two parameters, `base` and `text`, both of which can be any type
-/// that implements the [`AsRef<str>`]  trait. It returns a new `String` that is the concatenation
-/// of `base` and `text`.
+/// that implements [`ConcatPart`], such as [`&str`](str) or [`String`]. It returns a new `String`
+/// that is the concatenation of `base` and `text`.
 ///
 /// # Examples
 ///
@@ -26,12 +120,12 @@ use alloc::string::String;
 #[cfg_attr(feature = "no-inline-concat", inline(never))]
 pub fn concat_2<S1, S2>(base: S1, text: S2) -> String
 where
-    S1: AsRef<str>,
-    S2: AsRef<str>,
+    S1: ConcatPart,
+    S2: ConcatPart,
 {
     // https://godbolt.org/z/EYKn66ahW
-    let base = base.as_ref();
-    let text = text.as_ref();
+    let base = base.to_part();
+    let text = text.to_part();
     let total_length = base.len() + text.len();
 
     let mut result = String::with_capacity(total_length);
@@ -43,8 +137,8 @@ where
         vec.set_len(total_length);
 
         // Manually copy the bytes.
-        core::ptr::copy_nonoverlapping(base.as_ptr(), vec.as_mut_ptr(), base.len());
-        core::ptr::copy_nonoverlapping(text.as_ptr(), vec.as_mut_ptr().add(base.len()), text.len());
+        base.write(vec.as_mut_ptr());
+        text.write(vec.as_mut_ptr().add(base.len()));
     }
 
     result
@@ -53,8 +147,8 @@ where
 /// Concatenates three strings using unsafe Rust for better performance.
 ///
 /// This function takes three parameters, `base`, `middle`, and `end`, all of which can be any type
-/// that implements the [`AsRef<str>`] trait. It returns a new `String` that is the concatenation
-/// of `base`, `middle`, and `end`.
+/// that implements [`ConcatPart`], such as [`&str`](str) or [`String`]. It returns a new `String`
+/// that is the concatenation of `base`, `middle`, and `end`.
 ///
 /// # Examples
 ///
@@ -78,13 +172,13 @@ where
 #[cfg_attr(feature = "no-inline-concat", inline(never))]
 pub fn concat_3<S1, S2, S3>(base: S1, middle: S2, end: S3) -> String
 where
-    S1: AsRef<str>,
-    S2: AsRef<str>,
-    S3: AsRef<str>,
+    S1: ConcatPart,
+    S2: ConcatPart,
+    S3: ConcatPart,
 {
-    let base = base.as_ref();
-    let middle = middle.as_ref();
-    let end = end.as_ref();
+    let base = base.to_part();
+    let middle = middle.to_part();
+    let end = end.to_part();
     let total_length = base.len() + middle.len() + end.len();
     let mut result = String::with_capacity(total_length);
 
@@ -96,11 +190,11 @@ where
 
         // Manually copy the bytes
         let mut pos = 0;
-        core::ptr::copy_nonoverlapping(base.as_ptr(), vec.as_mut_ptr(), base.len());
+        base.write(vec.as_mut_ptr());
         pos += base.len();
-        core::ptr::copy_nonoverlapping(middle.as_ptr(), vec.as_mut_ptr().add(pos), middle.len());
+        middle.write(vec.as_mut_ptr().add(pos));
         pos += middle.len();
-        core::ptr::copy_nonoverlapping(end.as_ptr(), vec.as_mut_ptr().add(pos), end.len());
+        end.write(vec.as_mut_ptr().add(pos));
     }
 
     result
@@ -109,8 +203,8 @@ where
 /// Concatenates four strings using unsafe Rust for better performance.
 ///
 /// This function takes four parameters, `s1`, `s2`, `s3`, and `s4`, all of which can be any type
-/// that implements the [`AsRef<str>`] trait. It returns a new `String` that is the concatenation
-/// of `s1`, `s2`, `s3`, and `s4`.
+/// that implements [`ConcatPart`], such as [`&str`](str) or [`String`]. It returns a new `String`
+/// that is the concatenation of `s1`, `s2`, `s3`, and `s4`.
 ///
 /// # Examples
 ///
@@ -136,15 +230,15 @@ where
 #[cfg_attr(feature = "no-inline-concat", inline(never))]
 pub fn concat_4<S1, S2, S3, S4>(s1: S1, s2: S2, s3: S3, s4: S4) -> String
 where
-    S1: AsRef<str>,
-    S2: AsRef<str>,
-    S3: AsRef<str>,
-    S4: AsRef<str>,
+    S1: ConcatPart,
+    S2: ConcatPart,
+    S3: ConcatPart,
+    S4: ConcatPart,
 {
-    let s1 = s1.as_ref();
-    let s2 = s2.as_ref();
-    let s3 = s3.as_ref();
-    let s4 = s4.as_ref();
+    let s1 = s1.to_part();
+    let s2 = s2.to_part();
+    let s3 = s3.to_part();
+    let s4 = s4.to_part();
     let total_length = s1.len() + s2.len() + s3.len() + s4.len();
     let mut result = String::with_capacity(total_length);
 
@@ -156,13 +250,13 @@ where
 
         // Manually copy the bytes
         let mut pos = 0;
-        core::ptr::copy_nonoverlapping(s1.as_ptr(), vec.as_mut_ptr(), s1.len());
+        s1.write(vec.as_mut_ptr());
         pos += s1.len();
-        core::ptr::copy_nonoverlapping(s2.as_ptr(), vec.as_mut_ptr().add(pos), s2.len());
+        s2.write(vec.as_mut_ptr().add(pos));
         pos += s2.len();
-        core::ptr::copy_nonoverlapping(s3.as_ptr(), vec.as_mut_ptr().add(pos), s3.len());
+        s3.write(vec.as_mut_ptr().add(pos));
         pos += s3.len();
-        core::ptr::copy_nonoverlapping(s4.as_ptr(), vec.as_mut_ptr().add(pos), s4.len());
+        s4.write(vec.as_mut_ptr().add(pos));
     }
 
     result
@@ -171,8 +265,8 @@ where
 /// Concatenates five strings using unsafe Rust for better performance.
 ///
 /// This function takes five parameters, `s1`, `s2`, `s3`, `s4`, and `s5`, all of which can be any type
-/// that implements the [`AsRef<str>`] trait. It returns a new `String` that is the concatenation
-/// of `s1`, `s2`, `s3`, `s4`, and `s5`.
+/// that implements [`ConcatPart`], such as [`&str`](str) or [`String`]. It returns a new `String`
+/// that is the concatenation of `s1`, `s2`, `s3`, `s4`, and `s5`.
 ///
 /// # Examples
 ///
@@ -200,17 +294,17 @@ where
 #[cfg_attr(feature = "no-inline-concat", inline(never))]
 pub fn concat_5<S1, S2, S3, S4, S5>(s1: S1, s2: S2, s3: S3, s4: S4, s5: S5) -> String
 where
-    S1: AsRef<str>,
-    S2: AsRef<str>,
-    S3: AsRef<str>,
-    S4: AsRef<str>,
-    S5: AsRef<str>,
+    S1: ConcatPart,
+    S2: ConcatPart,
+    S3: ConcatPart,
+    S4: ConcatPart,
+    S5: ConcatPart,
 {
-    let s1 = s1.as_ref();
-    let s2 = s2.as_ref();
-    let s3 = s3.as_ref();
-    let s4 = s4.as_ref();
-    let s5 = s5.as_ref();
+    let s1 = s1.to_part();
+    let s2 = s2.to_part();
+    let s3 = s3.to_part();
+    let s4 = s4.to_part();
+    let s5 = s5.to_part();
     let total_length = s1.len() + s2.len() + s3.len() + s4.len() + s5.len();
     let mut result = String::with_capacity(total_length);
 
@@ -222,20 +316,343 @@ where
 
         // Manually copy the bytes
         let mut pos = 0;
-        core::ptr::copy_nonoverlapping(s1.as_ptr(), vec.as_mut_ptr(), s1.len());
+        s1.write(vec.as_mut_ptr());
         pos += s1.len();
-        core::ptr::copy_nonoverlapping(s2.as_ptr(), vec.as_mut_ptr().add(pos), s2.len());
+        s2.write(vec.as_mut_ptr().add(pos));
         pos += s2.len();
-        core::ptr::copy_nonoverlapping(s3.as_ptr(), vec.as_mut_ptr().add(pos), s3.len());
+        s3.write(vec.as_mut_ptr().add(pos));
         pos += s3.len();
-        core::ptr::copy_nonoverlapping(s4.as_ptr(), vec.as_mut_ptr().add(pos), s4.len());
+        s4.write(vec.as_mut_ptr().add(pos));
         pos += s4.len();
-        core::ptr::copy_nonoverlapping(s5.as_ptr(), vec.as_mut_ptr().add(pos), s5.len());
+        s5.write(vec.as_mut_ptr().add(pos));
     }
 
     result
 }
 
+/// A growable string builder that stores short strings inline.
+///
+/// Up to `N` bytes are stored inside the builder itself; once the string outgrows that,
+/// its contents are moved to the heap, where they stay. The `push_N` methods take any
+/// [`ConcatPart`], and reserve room for the whole group of parts at once.
+///
+/// Use this when a string can't be built in a single `concat_N` call, e.g. when it is
+/// built across branches.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::string_concat::StringBuilder;
+/// let is_admin = true;
+/// let mut builder = StringBuilder::<32>::new();
+/// builder.push_2("user: ", "alice");
+/// if is_admin {
+///     builder.push_3(" (", "admin", ")");
+/// }
+/// assert!(builder.is_inline());
+/// assert_eq!(builder.finish(), "user: alice (admin)");
+/// ```
+pub struct StringBuilder<const N: usize = 32> {
+    inner: BuilderStorage<N>,
+}
+
+enum BuilderStorage<const N: usize> {
+    Inline {
+        buf: [MaybeUninit<u8>; N],
+        len: usize,
+    },
+    Heap(String),
+}
+
+impl<const N: usize> StringBuilder<N> {
+    /// Creates an empty builder, which stores its contents inline.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: BuilderStorage::Inline {
+                buf: [MaybeUninit::uninit(); N],
+                len: 0,
+            },
+        }
+    }
+
+    /// Creates an empty builder with room for at least `capacity` bytes.
+    ///
+    /// If `capacity` exceeds `N`, the builder starts out on the heap.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            return Self::new();
+        }
+
+        Self {
+            inner: BuilderStorage::Heap(String::with_capacity(capacity)),
+        }
+    }
+
+    /// Returns the length of the string built so far, in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match &self.inner {
+            BuilderStorage::Inline { len, .. } => *len,
+            BuilderStorage::Heap(string) => string.len(),
+        }
+    }
+
+    /// Returns `true` if nothing has been pushed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bytes the builder can hold without allocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        match &self.inner {
+            BuilderStorage::Inline { .. } => N,
+            BuilderStorage::Heap(string) => string.capacity(),
+        }
+    }
+
+    /// Returns `true` if the contents are still stored inline.
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        matches!(self.inner, BuilderStorage::Inline { .. })
+    }
+
+    /// Returns the string built so far.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match &self.inner {
+            // SAFETY: The first `len` bytes are initialized with valid UTF-8.
+            BuilderStorage::Inline { buf, len } => unsafe {
+                core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+                    buf.as_ptr() as *const u8,
+                    *len,
+                ))
+            },
+            BuilderStorage::Heap(string) => string.as_str(),
+        }
+    }
+
+    /// Removes the contents of the builder, keeping its storage.
+    #[inline]
+    pub fn clear(&mut self) {
+        match &mut self.inner {
+            BuilderStorage::Inline { len, .. } => *len = 0,
+            BuilderStorage::Heap(string) => string.clear(),
+        }
+    }
+
+    /// Appends a string.
+    #[inline]
+    pub fn push<S1>(&mut self, s1: S1)
+    where
+        S1: ConcatPart,
+    {
+        self.push_parts(&[s1.to_part()]);
+    }
+
+    /// Appends two strings, reserving room for both at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::string_concat::StringBuilder;
+    /// let mut builder = StringBuilder::<16>::new();
+    /// builder.push_2("Hello, ", "world!");
+    /// assert_eq!(builder.as_str(), "Hello, world!");
+    /// ```
+    #[cfg_attr(feature = "no-inline-concat", inline(never))]
+    pub fn push_2<S1, S2>(&mut self, s1: S1, s2: S2)
+    where
+        S1: ConcatPart,
+        S2: ConcatPart,
+    {
+        self.push_parts(&[s1.to_part(), s2.to_part()]);
+    }
+
+    /// Appends three strings, reserving room for all of them at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::string_concat::StringBuilder;
+    /// let mut builder = StringBuilder::<16>::new();
+    /// builder.push_3("a", "b", "c");
+    /// assert_eq!(builder.as_str(), "abc");
+    /// ```
+    #[cfg_attr(feature = "no-inline-concat", inline(never))]
+    pub fn push_3<S1, S2, S3>(&mut self, s1: S1, s2: S2, s3: S3)
+    where
+        S1: ConcatPart,
+        S2: ConcatPart,
+        S3: ConcatPart,
+    {
+        self.push_parts(&[s1.to_part(), s2.to_part(), s3.to_part()]);
+    }
+
+    /// Appends four strings, reserving room for all of them at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::string_concat::StringBuilder;
+    /// let mut builder = StringBuilder::<16>::new();
+    /// builder.push_4("a", "b", "c", "d");
+    /// assert_eq!(builder.as_str(), "abcd");
+    /// ```
+    #[cfg_attr(feature = "no-inline-concat", inline(never))]
+    pub fn push_4<S1, S2, S3, S4>(&mut self, s1: S1, s2: S2, s3: S3, s4: S4)
+    where
+        S1: ConcatPart,
+        S2: ConcatPart,
+        S3: ConcatPart,
+        S4: ConcatPart,
+    {
+        self.push_parts(&[s1.to_part(), s2.to_part(), s3.to_part(), s4.to_part()]);
+    }
+
+    /// Appends five strings, reserving room for all of them at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanokit::string_concat::StringBuilder;
+    /// let mut builder = StringBuilder::<16>::new();
+    /// builder.push_5("a", "b", "c", "d", "e");
+    /// assert_eq!(builder.as_str(), "abcde");
+    /// ```
+    #[cfg_attr(feature = "no-inline-concat", inline(never))]
+    pub fn push_5<S1, S2, S3, S4, S5>(&mut self, s1: S1, s2: S2, s3: S3, s4: S4, s5: S5)
+    where
+        S1: ConcatPart,
+        S2: ConcatPart,
+        S3: ConcatPart,
+        S4: ConcatPart,
+        S5: ConcatPart,
+    {
+        self.push_parts(&[
+            s1.to_part(),
+            s2.to_part(),
+            s3.to_part(),
+            s4.to_part(),
+            s5.to_part(),
+        ]);
+    }
+
+    /// Converts the builder into a [`String`].
+    ///
+    /// If the contents are already on the heap, that allocation is returned as-is;
+    /// otherwise a `String` of the exact length is allocated.
+    #[inline]
+    pub fn finish(self) -> String {
+        match self.inner {
+            BuilderStorage::Inline { .. } => concat_2(self.as_str(), ""),
+            BuilderStorage::Heap(string) => string,
+        }
+    }
+
+    #[inline(always)]
+    fn push_parts(&mut self, parts: &[Part<'_>]) {
+        let total_length: usize = parts.iter().map(|p| p.len()).sum();
+        let len = self.len();
+
+        unsafe {
+            let mut dst = self.reserve_for(total_length);
+            for part in parts {
+                part.write(dst);
+                dst = dst.add(part.len());
+            }
+            self.set_len(len + total_length);
+        }
+    }
+
+    /// Ensures there is room for `additional` more bytes, and returns a pointer to the end
+    /// of the string.
+    #[inline(always)]
+    fn reserve_for(&mut self, additional: usize) -> *mut u8 {
+        match &mut self.inner {
+            BuilderStorage::Inline { buf, len } if *len + additional <= N => unsafe {
+                (buf.as_mut_ptr() as *mut u8).add(*len)
+            },
+            BuilderStorage::Inline { .. } => self.spill(additional),
+            BuilderStorage::Heap(string) => {
+                string.reserve(additional);
+                unsafe { string.as_mut_vec().as_mut_ptr().add(string.len()) }
+            }
+        }
+    }
+
+    /// Moves the contents to the heap, with room for `additional` more bytes.
+    #[cold]
+    #[inline(never)]
+    fn spill(&mut self, additional: usize) -> *mut u8 {
+        let len = self.len();
+        let mut string = String::with_capacity((len + additional).max(N * 2));
+        string.push_str(self.as_str());
+        self.inner = BuilderStorage::Heap(string);
+
+        match &mut self.inner {
+            BuilderStorage::Heap(string) => unsafe { string.as_mut_vec().as_mut_ptr().add(len) },
+            BuilderStorage::Inline { .. } => unreachable!(),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The first `new_len` bytes must be initialized with valid UTF-8, within capacity.
+    #[inline(always)]
+    unsafe fn set_len(&mut self, new_len: usize) {
+        match &mut self.inner {
+            BuilderStorage::Inline { len, .. } => *len = new_len,
+            BuilderStorage::Heap(string) => string.as_mut_vec().set_len(new_len),
+        }
+    }
+}
+
+impl<const N: usize> Default for StringBuilder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for StringBuilder<N> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for StringBuilder<N> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Write for StringBuilder<N> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push(s);
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Debug for StringBuilder<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for StringBuilder<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -392,4 +809,92 @@ mod tests {
         let result = concat_5(s1, s2, s3, s4, s5);
         assert_eq!(result, "ABCDE");
     }
+
+    #[test]
+    fn test_string_builder_stays_inline() {
+        let mut builder = StringBuilder::<8>::new();
+        builder.push_2("ab", "cd");
+        builder.push_4("e", "f", "", "gh");
+        assert!(builder.is_inline());
+        assert_eq!(builder.len(), 8);
+        assert_eq!(builder.capacity(), 8);
+
+        let result = builder.finish();
+        assert_eq!(result, "abcdefgh");
+        assert_eq!(result.capacity(), 8);
+    }
+
+    #[test]
+    fn test_string_builder_spills_to_heap() {
+        let mut builder = StringBuilder::<4>::new();
+        builder.push_2("ab", "c");
+        builder.push_3("de", "f", "g");
+        assert!(!builder.is_inline());
+        assert_eq!(builder.as_str(), "abcdefg");
+
+        builder.push_5("1", "2", "3", "4", "5");
+        assert_eq!(builder.as_str(), "abcdefg12345");
+
+        let ptr = builder.as_ptr();
+        let result = builder.finish();
+        assert_eq!(result, "abcdefg12345");
+        assert_eq!(result.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_string_builder_filled_to_capacity() {
+        let mut builder = StringBuilder::<4>::with_capacity(10);
+        let ptr = builder.as_ptr();
+        builder.push_2("01234", "56");
+        builder.push_3("7", "8", "9");
+        assert_eq!(builder.as_ptr(), ptr);
+        assert_eq!(builder.capacity(), 10);
+
+        let result = builder.finish();
+        assert_eq!(result, "0123456789");
+        assert_eq!(result.as_ptr(), ptr);
+        assert_eq!(result.capacity(), 10);
+    }
+
+    #[test]
+    fn test_string_builder_with_capacity() {
+        let builder = StringBuilder::<4>::with_capacity(4);
+        assert!(builder.is_inline());
+
+        let mut builder = StringBuilder::<4>::with_capacity(100);
+        assert!(!builder.is_inline());
+        builder.push("a");
+        assert_eq!(builder.capacity(), 100);
+        assert_eq!(builder.finish().capacity(), 100);
+    }
+
+    #[test]
+    fn test_string_builder_zero_inline() {
+        let mut builder = StringBuilder::<0>::new();
+        assert!(builder.is_empty());
+        builder.push_2("", "");
+        assert!(builder.is_inline());
+        builder.push("x");
+        assert!(!builder.is_inline());
+        assert_eq!(builder.finish(), "x");
+    }
+
+    #[test]
+    fn test_string_builder_clear_and_write() {
+        use core::fmt::Write;
+
+        let mut builder = StringBuilder::<16>::default();
+        let name = "two";
+        write!(builder, "{}-{name}", 1).unwrap();
+        assert_eq!(builder.as_str(), "1-two");
+
+        builder.clear();
+        assert!(builder.is_empty());
+        builder.push(crate::string_escape::XmlEscaped("<b>"));
+        assert_eq!(&*builder, "&lt;b&gt;");
+        assert_eq!(
+            alloc::format!("{builder} {builder:?}"),
+            "&lt;b&gt; \"&lt;b&gt;\""
+        );
+    }
 }
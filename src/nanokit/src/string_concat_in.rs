@@ -0,0 +1,371 @@
+use crate::string_concat::ConcatPart;
+use core::{fmt, ops::Deref};
+
+#[cfg(feature = "allocator-api2")]
+use allocator_api2::{alloc::Allocator, vec::Vec};
+#[cfg(not(feature = "allocator-api2"))]
+use {alloc::vec::Vec, core::alloc::Allocator};
+
+/// A UTF-8 string stored in a [`Vec<u8, A>`](Vec), allocated with a custom allocator.
+///
+/// Returned by the `concat_N_in` functions. Dereferences to [`str`].
+#[derive(Clone)]
+pub struct StringIn<A: Allocator> {
+    vec: Vec<u8, A>,
+}
+
+impl<A: Allocator> StringIn<A> {
+    /// Creates a string from a vector of bytes without checking that it contains valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must contain valid UTF-8.
+    pub unsafe fn from_utf8_unchecked(bytes: Vec<u8, A>) -> Self {
+        Self { vec: bytes }
+    }
+
+    /// Returns the contents of this string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: Only ever constructed from valid UTF-8.
+        unsafe { core::str::from_utf8_unchecked(&self.vec) }
+    }
+
+    /// Returns the allocator backing this string.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
+    }
+
+    /// Converts this string into its underlying vector of bytes.
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8, A> {
+        self.vec
+    }
+}
+
+impl<A: Allocator> Deref for StringIn<A> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<A: Allocator> AsRef<str> for StringIn<A> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<A: Allocator> fmt::Debug for StringIn<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<A: Allocator> fmt::Display for StringIn<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<A: Allocator, B: Allocator> PartialEq<StringIn<B>> for StringIn<A> {
+    fn eq(&self, other: &StringIn<B>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<A: Allocator> Eq for StringIn<A> {}
+
+impl<A: Allocator> PartialEq<str> for StringIn<A> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<A: Allocator> PartialEq<&str> for StringIn<A> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Concatenates two strings into memory allocated by `alloc`.
+///
+/// This works just like [`concat_2`], except the result is allocated with the given allocator.
+/// On stable this uses the [`allocator-api2`] crate's `Allocator` trait, on nightly (with the
+/// `nightly` feature) it uses [`core::alloc::Allocator`].
+///
+/// # Examples
+///
+/// ```
+/// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+/// # #[cfg(feature = "allocator-api2")]
+/// # use allocator_api2::alloc::Global;
+/// # #[cfg(not(feature = "allocator-api2"))]
+/// # use std::alloc::Global;
+/// use nanokit::string_concat_in::concat_2_in;
+/// let result = concat_2_in(Global, "Hello, ", "world!");
+/// assert_eq!(result, "Hello, world!");
+/// ```
+///
+/// [`concat_2`]: crate::string_concat::concat_2
+/// [`allocator-api2`]: https://crates.io/crates/allocator-api2
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn concat_2_in<A, S1, S2>(alloc: A, s1: S1, s2: S2) -> StringIn<A>
+where
+    A: Allocator,
+    S1: ConcatPart,
+    S2: ConcatPart,
+{
+    let s1 = s1.to_part();
+    let s2 = s2.to_part();
+    let total_length = s1.len() + s2.len();
+    let mut vec = Vec::with_capacity_in(total_length, alloc);
+
+    unsafe {
+        // Ensure that the vector has enough capacity
+        vec.set_len(total_length);
+
+        // Manually copy the bytes.
+        s1.write(vec.as_mut_ptr());
+        s2.write(vec.as_mut_ptr().add(s1.len()));
+
+        StringIn::from_utf8_unchecked(vec)
+    }
+}
+
+/// Concatenates three strings into memory allocated by `alloc`.
+///
+/// See [`concat_2_in`] for details.
+///
+/// # Examples
+///
+/// ```
+/// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+/// # #[cfg(feature = "allocator-api2")]
+/// # use allocator_api2::alloc::Global;
+/// # #[cfg(not(feature = "allocator-api2"))]
+/// # use std::alloc::Global;
+/// use nanokit::string_concat_in::concat_3_in;
+/// let result = concat_3_in(Global, "core", "::", "alloc");
+/// assert_eq!(result, "core::alloc");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn concat_3_in<A, S1, S2, S3>(alloc: A, s1: S1, s2: S2, s3: S3) -> StringIn<A>
+where
+    A: Allocator,
+    S1: ConcatPart,
+    S2: ConcatPart,
+    S3: ConcatPart,
+{
+    let s1 = s1.to_part();
+    let s2 = s2.to_part();
+    let s3 = s3.to_part();
+    let total_length = s1.len() + s2.len() + s3.len();
+    let mut vec = Vec::with_capacity_in(total_length, alloc);
+
+    unsafe {
+        // Ensure that the vector has enough capacity
+        vec.set_len(total_length);
+
+        // Manually copy the bytes
+        let mut pos = 0;
+        s1.write(vec.as_mut_ptr());
+        pos += s1.len();
+        s2.write(vec.as_mut_ptr().add(pos));
+        pos += s2.len();
+        s3.write(vec.as_mut_ptr().add(pos));
+
+        StringIn::from_utf8_unchecked(vec)
+    }
+}
+
+/// Concatenates four strings into memory allocated by `alloc`.
+///
+/// See [`concat_2_in`] for details.
+///
+/// # Examples
+///
+/// ```
+/// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+/// # #[cfg(feature = "allocator-api2")]
+/// # use allocator_api2::alloc::Global;
+/// # #[cfg(not(feature = "allocator-api2"))]
+/// # use std::alloc::Global;
+/// use nanokit::string_concat_in::concat_4_in;
+/// let result = concat_4_in(Global, "The", " quick", " brown", " fox");
+/// assert_eq!(result, "The quick brown fox");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn concat_4_in<A, S1, S2, S3, S4>(alloc: A, s1: S1, s2: S2, s3: S3, s4: S4) -> StringIn<A>
+where
+    A: Allocator,
+    S1: ConcatPart,
+    S2: ConcatPart,
+    S3: ConcatPart,
+    S4: ConcatPart,
+{
+    let s1 = s1.to_part();
+    let s2 = s2.to_part();
+    let s3 = s3.to_part();
+    let s4 = s4.to_part();
+    let total_length = s1.len() + s2.len() + s3.len() + s4.len();
+    let mut vec = Vec::with_capacity_in(total_length, alloc);
+
+    unsafe {
+        // Ensure that the vector has enough capacity
+        vec.set_len(total_length);
+
+        // Manually copy the bytes
+        let mut pos = 0;
+        s1.write(vec.as_mut_ptr());
+        pos += s1.len();
+        s2.write(vec.as_mut_ptr().add(pos));
+        pos += s2.len();
+        s3.write(vec.as_mut_ptr().add(pos));
+        pos += s3.len();
+        s4.write(vec.as_mut_ptr().add(pos));
+
+        StringIn::from_utf8_unchecked(vec)
+    }
+}
+
+/// Concatenates five strings into memory allocated by `alloc`.
+///
+/// See [`concat_2_in`] for details.
+///
+/// # Examples
+///
+/// ```
+/// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+/// # #[cfg(feature = "allocator-api2")]
+/// # use allocator_api2::alloc::Global;
+/// # #[cfg(not(feature = "allocator-api2"))]
+/// # use std::alloc::Global;
+/// use nanokit::string_concat_in::concat_5_in;
+/// let result = concat_5_in(Global, "The", " quick", " brown", " fox", " jumps");
+/// assert_eq!(result, "The quick brown fox jumps");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn concat_5_in<A, S1, S2, S3, S4, S5>(
+    alloc: A,
+    s1: S1,
+    s2: S2,
+    s3: S3,
+    s4: S4,
+    s5: S5,
+) -> StringIn<A>
+where
+    A: Allocator,
+    S1: ConcatPart,
+    S2: ConcatPart,
+    S3: ConcatPart,
+    S4: ConcatPart,
+    S5: ConcatPart,
+{
+    let s1 = s1.to_part();
+    let s2 = s2.to_part();
+    let s3 = s3.to_part();
+    let s4 = s4.to_part();
+    let s5 = s5.to_part();
+    let total_length = s1.len() + s2.len() + s3.len() + s4.len() + s5.len();
+    let mut vec = Vec::with_capacity_in(total_length, alloc);
+
+    unsafe {
+        // Ensure that the vector has enough capacity
+        vec.set_len(total_length);
+
+        // Manually copy the bytes
+        let mut pos = 0;
+        s1.write(vec.as_mut_ptr());
+        pos += s1.len();
+        s2.write(vec.as_mut_ptr().add(pos));
+        pos += s2.len();
+        s3.write(vec.as_mut_ptr().add(pos));
+        pos += s3.len();
+        s4.write(vec.as_mut_ptr().add(pos));
+        pos += s4.len();
+        s5.write(vec.as_mut_ptr().add(pos));
+
+        StringIn::from_utf8_unchecked(vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string_escape::XmlEscaped;
+    use alloc::string::String;
+    use core::{alloc::Layout, cell::Cell, ptr::NonNull};
+
+    #[cfg(feature = "allocator-api2")]
+    use allocator_api2::alloc::{AllocError, Global};
+    #[cfg(not(feature = "allocator-api2"))]
+    use {alloc::alloc::Global, core::alloc::AllocError};
+
+    /// Counts the bytes allocated through it, and forwards to [`Global`].
+    #[derive(Default)]
+    struct CountingAlloc {
+        allocated: Cell<usize>,
+    }
+
+    unsafe impl Allocator for &CountingAlloc {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.allocated.set(self.allocated.get() + layout.size());
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn test_concat_n_in_uses_allocator() {
+        let counter = CountingAlloc::default();
+
+        let result = concat_2_in(&counter, "Hello, ", String::from("world!"));
+        assert_eq!(result, "Hello, world!");
+        assert_eq!(counter.allocated.get(), 13);
+
+        let result = concat_3_in(&counter, "a", "b", "c");
+        assert_eq!(result, "abc");
+        assert_eq!(counter.allocated.get(), 16);
+
+        let result = concat_4_in(&counter, "a", "b", "c", "d");
+        assert_eq!(result, "abcd");
+        assert_eq!(counter.allocated.get(), 20);
+
+        let result = concat_5_in(&counter, "a", "b", "c", "d", "e");
+        assert_eq!(result.as_str(), "abcde");
+        assert_eq!(counter.allocated.get(), 25);
+    }
+
+    #[test]
+    fn test_concat_n_in_empty_strings() {
+        let counter = CountingAlloc::default();
+        let result = concat_5_in(&counter, "", "", "", "", "");
+        assert_eq!(result, "");
+        assert_eq!(counter.allocated.get(), 0);
+    }
+
+    #[test]
+    fn test_concat_n_in_escaped_parts() {
+        let result = concat_3_in(Global, "<a>", XmlEscaped("1 < 2"), "</a>");
+        assert_eq!(result, "<a>1 &lt; 2</a>");
+        assert_eq!(result.into_bytes().capacity(), 15);
+    }
+
+    #[test]
+    fn test_string_in_traits() {
+        let a = concat_2_in(Global, "a", "b");
+        let b = a.clone();
+        assert_eq!(a, b);
+        assert_eq!(alloc::format!("{a} {a:?}"), "ab \"ab\"");
+        assert_eq!(a.len(), 2);
+        assert_eq!(crate::string_concat::concat_2(&a, b), "abab");
+    }
+}
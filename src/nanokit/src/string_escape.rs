@@ -0,0 +1,306 @@
+use crate::string_concat::{ConcatPart, Part};
+
+/// Escapes its text for use inside a JSON string literal.
+///
+/// `"` and `\` are backslash-escaped, and control characters are written as `\n`, `\t`, etc.
+/// or `\u00XX`. The surrounding quotes are not added.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::string_concat::concat_3;
+/// use nanokit::string_escape::JsonEscaped;
+/// let name = "Bob \"The Builder\"\n";
+/// let result = concat_3("{\"name\":\"", JsonEscaped(name), "\"}");
+/// assert_eq!(result, r#"{"name":"Bob \"The Builder\"\n"}"#);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct JsonEscaped<S>(pub S);
+
+/// Quotes its text as a single POSIX shell word.
+///
+/// The text is wrapped in single quotes, and embedded single quotes are written as `'\''`.
+/// Everything else is taken literally by the shell.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::string_concat::concat_2;
+/// use nanokit::string_escape::ShellQuoted;
+/// let file = "it's $HOME.txt";
+/// let result = concat_2("cat ", ShellQuoted(file));
+/// assert_eq!(result, r"cat 'it'\''s $HOME.txt'");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ShellQuoted<S>(pub S);
+
+/// Escapes its text for use in XML content or attribute values.
+///
+/// `&`, `<`, `>`, `"` and `'` are replaced with their predefined entities.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::string_concat::concat_3;
+/// use nanokit::string_escape::XmlEscaped;
+/// let title = "Tom & Jerry's <Show>";
+/// let result = concat_3("<title>", XmlEscaped(title), "</title>");
+/// assert_eq!(result, "<title>Tom &amp; Jerry&apos;s &lt;Show&gt;</title>");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct XmlEscaped<S>(pub S);
+
+impl<S: AsRef<str>> ConcatPart for JsonEscaped<S> {
+    #[inline]
+    fn to_part(&self) -> Part<'_> {
+        Part::escaped(self.0.as_ref(), Escape::Json)
+    }
+}
+
+impl<S: AsRef<str>> ConcatPart for ShellQuoted<S> {
+    #[inline]
+    fn to_part(&self) -> Part<'_> {
+        Part::escaped(self.0.as_ref(), Escape::Shell)
+    }
+}
+
+impl<S: AsRef<str>> ConcatPart for XmlEscaped<S> {
+    #[inline]
+    fn to_part(&self) -> Part<'_> {
+        Part::escaped(self.0.as_ref(), Escape::Xml)
+    }
+}
+
+/// How the text of a [`Part`] is transformed while it is copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Escape {
+    None,
+    Json,
+    Shell,
+    Xml,
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Returns the length of `text` once escaped with `escape`.
+pub(crate) fn escaped_len(text: &str, escape: Escape) -> usize {
+    let text = text.as_bytes();
+    match escape {
+        Escape::None => text.len(),
+        Escape::Json => text.len() + text.iter().map(|&b| json_extra_len(b)).sum::<usize>(),
+        Escape::Shell => text.len() + 2 + text.iter().map(|&b| shell_extra_len(b)).sum::<usize>(),
+        Escape::Xml => text.len() + text.iter().map(|&b| xml_extra_len(b)).sum::<usize>(),
+    }
+}
+
+/// Writes `text` escaped with `escape` to `dst`.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of [`escaped_len`] bytes.
+pub(crate) unsafe fn write_escaped(dst: *mut u8, text: &str, escape: Escape) {
+    let text = text.as_bytes();
+    match escape {
+        Escape::None => core::ptr::copy_nonoverlapping(text.as_ptr(), dst, text.len()),
+        Escape::Json => {
+            write_with(dst, text, json_extra_len, write_json_sequence);
+        }
+        Escape::Shell => {
+            *dst = b'\'';
+            let written = write_with(dst.add(1), text, shell_extra_len, write_shell_sequence);
+            *dst.add(1 + written) = b'\'';
+        }
+        Escape::Xml => {
+            write_with(dst, text, xml_extra_len, write_xml_sequence);
+        }
+    }
+}
+
+/// Copies `text` to `dst`, replacing every byte with a non-zero `extra_len` with the escape
+/// sequence written by `write_sequence`. Returns the number of bytes written.
+#[inline(always)]
+unsafe fn write_with(
+    dst: *mut u8,
+    text: &[u8],
+    extra_len: impl Fn(u8) -> usize,
+    write_sequence: impl Fn(u8, *mut u8) -> usize,
+) -> usize {
+    let mut pos = 0;
+    let mut run_start = 0;
+
+    for (x, &byte) in text.iter().enumerate() {
+        if extra_len(byte) == 0 {
+            continue;
+        }
+
+        // Copy the run of bytes that didn't need escaping in one go.
+        let run = &text[run_start..x];
+        core::ptr::copy_nonoverlapping(run.as_ptr(), dst.add(pos), run.len());
+        pos += run.len();
+        pos += write_sequence(byte, dst.add(pos));
+        run_start = x + 1;
+    }
+
+    let run = &text[run_start..];
+    core::ptr::copy_nonoverlapping(run.as_ptr(), dst.add(pos), run.len());
+    pos + run.len()
+}
+
+#[inline(always)]
+fn json_extra_len(byte: u8) -> usize {
+    match byte {
+        b'"' | b'\\' | 0x08 | 0x0C | b'\n' | b'\r' | b'\t' => 1,
+        0..=0x1F => 5,
+        _ => 0,
+    }
+}
+
+#[inline(always)]
+fn write_json_sequence(byte: u8, dst: *mut u8) -> usize {
+    let short = match byte {
+        b'"' => b'"',
+        b'\\' => b'\\',
+        0x08 => b'b',
+        0x0C => b'f',
+        b'\n' => b'n',
+        b'\r' => b'r',
+        b'\t' => b't',
+        _ => 0,
+    };
+
+    unsafe {
+        *dst = b'\\';
+        if short != 0 {
+            *dst.add(1) = short;
+            return 2;
+        }
+
+        core::ptr::copy_nonoverlapping(b"u00".as_ptr(), dst.add(1), 3);
+        *dst.add(4) = HEX_DIGITS[(byte >> 4) as usize];
+        *dst.add(5) = HEX_DIGITS[(byte & 0xF) as usize];
+        6
+    }
+}
+
+#[inline(always)]
+fn shell_extra_len(byte: u8) -> usize {
+    match byte {
+        b'\'' => 3,
+        _ => 0,
+    }
+}
+
+#[inline(always)]
+fn write_shell_sequence(_byte: u8, dst: *mut u8) -> usize {
+    // Close the quote, add an escaped quote, and reopen it.
+    unsafe { core::ptr::copy_nonoverlapping(br"'\''".as_ptr(), dst, 4) };
+    4
+}
+
+#[inline(always)]
+fn xml_extra_len(byte: u8) -> usize {
+    match byte {
+        b'&' => 4,
+        b'<' | b'>' => 3,
+        b'"' | b'\'' => 5,
+        _ => 0,
+    }
+}
+
+#[inline(always)]
+fn write_xml_sequence(byte: u8, dst: *mut u8) -> usize {
+    let entity: &[u8] = match byte {
+        b'&' => b"&amp;",
+        b'<' => b"&lt;",
+        b'>' => b"&gt;",
+        b'"' => b"&quot;",
+        _ => b"&apos;",
+    };
+
+    unsafe { core::ptr::copy_nonoverlapping(entity.as_ptr(), dst, entity.len()) };
+    entity.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string_concat::{concat_2, concat_3, concat_5};
+    use alloc::string::String;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", "")]
+    #[case("plain text", "plain text")]
+    #[case("\"", "\\\"")]
+    #[case("\\", "\\\\")]
+    #[case("a\nb\rc\td", "a\\nb\\rc\\td")]
+    #[case("\u{8}\u{c}", "\\b\\f")]
+    #[case("\u{0}\u{1F}", "\\u0000\\u001F")]
+    #[case("\u{7F}/", "\u{7F}/")]
+    #[case("ünïcödé \"", "ünïcödé \\\"")]
+    fn test_json_escaped(#[case] input: &str, #[case] expected: &str) {
+        let result = concat_2("", JsonEscaped(input));
+        assert_eq!(result, expected, "Failed for input: {input:?}");
+        assert_eq!(result.capacity(), result.len());
+    }
+
+    #[rstest]
+    #[case("", "''")]
+    #[case("plain", "'plain'")]
+    #[case("$HOME; rm -rf *", "'$HOME; rm -rf *'")]
+    #[case("it's", r"'it'\''s'")]
+    #[case("''", r"''\'''\'''")]
+    #[case("a\nb\\", "'a\nb\\'")]
+    fn test_shell_quoted(#[case] input: &str, #[case] expected: &str) {
+        let result = concat_2("", ShellQuoted(input));
+        assert_eq!(result, expected, "Failed for input: {input:?}");
+        assert_eq!(result.capacity(), result.len());
+    }
+
+    #[rstest]
+    #[case("", "")]
+    #[case("plain", "plain")]
+    #[case("a & b", "a &amp; b")]
+    #[case("<tag attr=\"x\">", "&lt;tag attr=&quot;x&quot;&gt;")]
+    #[case("it's", "it&apos;s")]
+    #[case("&amp;", "&amp;amp;")]
+    fn test_xml_escaped(#[case] input: &str, #[case] expected: &str) {
+        let result = concat_2("", XmlEscaped(input));
+        assert_eq!(result, expected, "Failed for input: {input:?}");
+        assert_eq!(result.capacity(), result.len());
+    }
+
+    #[test]
+    fn test_escaped_parts_mixed_with_plain_parts() {
+        let user = String::from("O'Brien <admin>");
+        let result = concat_5(
+            "{\"user\":\"",
+            JsonEscaped(&user),
+            "\",\"cmd\":\"",
+            JsonEscaped(concat_2("echo ", ShellQuoted(&user))),
+            "\"}",
+        );
+        assert_eq!(
+            result,
+            r#"{"user":"O'Brien <admin>","cmd":"echo 'O'\\''Brien <admin>'"}"#
+        );
+        assert_eq!(
+            concat_3("<a>", XmlEscaped(user), "</a>"),
+            "<a>O&apos;Brien &lt;admin&gt;</a>"
+        );
+    }
+
+    #[test]
+    fn test_escaped_len_all_bytes() {
+        // Every ASCII character, so every escape path is covered.
+        let text: String = (0..0x80u8).map(char::from).collect();
+        for escape in [Escape::None, Escape::Json, Escape::Shell, Escape::Xml] {
+            // Output is all ASCII, so the sentinel shows exactly how much was written.
+            let part = Part::escaped(&text, escape);
+            let mut buffer = alloc::vec![0xAAu8; part.len() + 16];
+            unsafe { part.write(buffer.as_mut_ptr()) };
+            let written = buffer.iter().position(|&b| b == 0xAA);
+            assert_eq!(written, Some(part.len()), "Failed for escape: {escape:?}");
+        }
+    }
+}
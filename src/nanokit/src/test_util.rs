@@ -0,0 +1,29 @@
+//! Helpers shared by the unit tests.
+
+use alloc::vec::Vec;
+
+/// Xorshift, so the random values are the same on every run.
+pub(crate) fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Returns `len` random words.
+pub(crate) fn random_words(len: usize, seed: u64) -> Vec<u64> {
+    let mut state = seed;
+    (0..len).map(|_| next_random(&mut state)).collect()
+}
+
+/// Returns `len` random bits packed into words, where roughly one in `sparsity` is set.
+pub(crate) fn random_bits(len: usize, sparsity: u64, seed: u64) -> Vec<u64> {
+    let mut state = seed;
+    let mut words = alloc::vec![0u64; len.div_ceil(64)];
+    for x in 0..len {
+        if next_random(&mut state).is_multiple_of(sparsity) {
+            words[x / 64] |= 1 << (x % 64);
+        }
+    }
+    words
+}
@@ -0,0 +1,422 @@
+use alloc::string::String;
+
+/// A set of ASCII bytes that are percent-encoded when copied.
+///
+/// Bytes outside the ASCII range are always percent-encoded, regardless of the set.
+/// Sets are built in `const` contexts from one of the predefined sets:
+///
+/// ```
+/// use nanokit::url_concat::{EncodeSet, PATH_SEGMENT};
+/// const MY_SET: EncodeSet = PATH_SEGMENT.add(b'+').remove(b'%');
+/// assert!(MY_SET.contains(b'+'));
+/// assert!(!MY_SET.contains(b'%'));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodeSet {
+    mask: u128,
+}
+
+impl EncodeSet {
+    /// A set that encodes no ASCII bytes.
+    pub const fn empty() -> Self {
+        Self { mask: 0 }
+    }
+
+    /// Returns a copy of this set which also encodes `byte`.
+    ///
+    /// Non-ASCII bytes are always encoded, so adding one returns the set unchanged.
+    pub const fn add(self, byte: u8) -> Self {
+        if byte >= 0x80 {
+            return self;
+        }
+        Self {
+            mask: self.mask | (1 << byte),
+        }
+    }
+
+    /// Returns a copy of this set which no longer encodes `byte`.
+    ///
+    /// Non-ASCII bytes cannot be removed from the set; removing one returns the set unchanged.
+    pub const fn remove(self, byte: u8) -> Self {
+        if byte >= 0x80 {
+            return self;
+        }
+        Self {
+            mask: self.mask & !(1 << byte),
+        }
+    }
+
+    /// Returns `true` if `byte` is percent-encoded by this set.
+    #[inline]
+    pub const fn contains(&self, byte: u8) -> bool {
+        byte >= 0x80 || (self.mask >> byte) & 1 != 0
+    }
+}
+
+/// The C0 control characters and `DEL`.
+pub const CONTROLS: EncodeSet = EncodeSet {
+    mask: 0xFFFF_FFFF | (1 << 0x7F),
+};
+
+/// The [query percent-encode set](https://url.spec.whatwg.org/#query-percent-encode-set).
+pub const QUERY: EncodeSet = CONTROLS.add(b' ').add(b'"').add(b'#').add(b'<').add(b'>');
+
+/// The [path percent-encode set](https://url.spec.whatwg.org/#path-percent-encode-set).
+pub const PATH: EncodeSet = QUERY.add(b'?').add(b'`').add(b'{').add(b'}');
+
+/// The [`PATH`] set, plus `/` and `%`, so each input becomes exactly one path segment.
+pub const PATH_SEGMENT: EncodeSet = PATH.add(b'/').add(b'%');
+
+/// Everything except the RFC 3986 unreserved characters (`A-Z a-z 0-9 - . _ ~`).
+///
+/// Use this for query keys and values, as it encodes `&`, `=` and `+`.
+pub const COMPONENT: EncodeSet = EncodeSet { mask: !UNRESERVED };
+
+const UNRESERVED: u128 = (0x3FF << b'0') // 0-9
+    | (0x3FF_FFFF << b'A') // A-Z
+    | (0x3FF_FFFF << b'a') // a-z
+    | (1 << b'-')
+    | (1 << b'.')
+    | (1 << b'_')
+    | (1 << b'~');
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Returns the length of `text` after percent-encoding it with `set`.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::url_concat::{encoded_len, PATH_SEGMENT};
+/// assert_eq!(encoded_len("a b/c", &PATH_SEGMENT), 9); // a%20b%2Fc
+/// ```
+#[inline]
+pub fn encoded_len(text: &str, set: &EncodeSet) -> usize {
+    // Each encoded byte grows by 2 (`%XX`).
+    text.len() + 2 * text.bytes().filter(|&b| set.contains(b)).count()
+}
+
+/// Percent-encodes `text` with `set`, allocating once.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::url_concat::{encode, PATH_SEGMENT};
+/// assert_eq!(encode("ümlaut & co/", &PATH_SEGMENT), "%C3%BCmlaut%20&%20co%2F");
+/// ```
+pub fn encode<S>(text: S, set: &EncodeSet) -> String
+where
+    S: AsRef<str>,
+{
+    let text = text.as_ref();
+    let total_length = encoded_len(text, set);
+    let mut result = String::with_capacity(total_length);
+
+    unsafe {
+        let vec = result.as_mut_vec();
+        vec.set_len(total_length);
+        write_encoded(vec.as_mut_ptr(), text.as_bytes(), set);
+    }
+
+    result
+}
+
+/// Appends a percent-encoded path segment to `base`, with exactly one `/` between them.
+///
+/// `base` is copied verbatim, minus a single trailing `/`. The segment is percent-encoded with
+/// `set` while it is copied, so the result is built with a single allocation.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::url_concat::{join_2, PATH_SEGMENT};
+/// let result = join_2("https://example.com/users/", "Jane Doe", &PATH_SEGMENT);
+/// assert_eq!(result, "https://example.com/users/Jane%20Doe");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_2<S1, S2>(base: S1, segment: S2, set: &EncodeSet) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+{
+    join_slice(base, &[segment.as_ref()], set)
+}
+
+/// Appends two percent-encoded path segments to `base`, with exactly one `/` between each of them.
+///
+/// See [`join_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::url_concat::{join_3, PATH_SEGMENT};
+/// let result = join_3("https://example.com/files", "a/b", "100%.txt", &PATH_SEGMENT);
+/// assert_eq!(result, "https://example.com/files/a%2Fb/100%25.txt");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_3<S1, S2, S3>(base: S1, s1: S2, s2: S3, set: &EncodeSet) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+{
+    join_slice(base, &[s1.as_ref(), s2.as_ref()], set)
+}
+
+/// Appends three percent-encoded path segments to `base`, with exactly one `/` between each of them.
+///
+/// See [`join_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::url_concat::{join_4, PATH_SEGMENT};
+/// let result = join_4("/api/", "v1", "repos", "my repo", &PATH_SEGMENT);
+/// assert_eq!(result, "/api/v1/repos/my%20repo");
+/// ```
+#[cfg_attr(feature = "no-inline-concat", inline(never))]
+pub fn join_4<S1, S2, S3, S4>(base: S1, s1: S2, s2: S3, s3: S4, set: &EncodeSet) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    S3: AsRef<str>,
+    S4: AsRef<str>,
+{
+    join_slice(base, &[s1.as_ref(), s2.as_ref(), s3.as_ref()], set)
+}
+
+/// Appends any number of percent-encoded path segments to `base`, with exactly one `/`
+/// between each of them.
+///
+/// The encoded length of every segment is computed up front, so this performs a single allocation.
+/// With no segments, `base` is returned unchanged. See [`join_2`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::url_concat::{join_slice, PATH_SEGMENT};
+/// let result = join_slice("https://example.com", &["a b", "c?d"], &PATH_SEGMENT);
+/// assert_eq!(result, "https://example.com/a%20b/c%3Fd");
+/// ```
+pub fn join_slice<B, S>(base: B, segments: &[S], set: &EncodeSet) -> String
+where
+    B: AsRef<str>,
+    S: AsRef<str>,
+{
+    let base = base.as_ref();
+    if segments.is_empty() {
+        return String::from(base);
+    }
+
+    let base = base.strip_suffix('/').unwrap_or(base);
+    let total_length = base.len()
+        + segments
+            .iter()
+            .map(|s| 1 + encoded_len(s.as_ref(), set))
+            .sum::<usize>();
+
+    let mut result = String::with_capacity(total_length);
+
+    unsafe {
+        let vec = result.as_mut_vec();
+        vec.set_len(total_length);
+
+        let dst = vec.as_mut_ptr();
+        core::ptr::copy_nonoverlapping(base.as_ptr(), dst, base.len());
+        let mut pos = base.len();
+        for segment in segments {
+            *dst.add(pos) = b'/';
+            pos += 1;
+            pos += write_encoded(dst.add(pos), segment.as_ref().as_bytes(), set);
+        }
+    }
+
+    result
+}
+
+/// Appends percent-encoded `key=value` pairs to the query string of `url`.
+///
+/// The pairs are joined with `&`, and prefixed with `?`, or with `&` if `url` already has a query.
+/// Keys and values are percent-encoded with `set` while they are copied, so the result is built
+/// with a single allocation. [`COMPONENT`] is usually the right set here.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::url_concat::{append_query, COMPONENT};
+/// let result = append_query("/search", &[("q", "rust & c++"), ("page", "2")], &COMPONENT);
+/// assert_eq!(result, "/search?q=rust%20%26%20c%2B%2B&page=2");
+///
+/// let result = append_query("/search?q=a", &[("lang", "en")], &COMPONENT);
+/// assert_eq!(result, "/search?q=a&lang=en");
+/// ```
+pub fn append_query<U, K, V>(url: U, pairs: &[(K, V)], set: &EncodeSet) -> String
+where
+    U: AsRef<str>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let url = url.as_ref();
+    let total_length = url.len()
+        + pairs
+            .iter()
+            .map(|(k, v)| 2 + encoded_len(k.as_ref(), set) + encoded_len(v.as_ref(), set))
+            .sum::<usize>();
+
+    let mut result = String::with_capacity(total_length);
+
+    unsafe {
+        let vec = result.as_mut_vec();
+        vec.set_len(total_length);
+
+        let dst = vec.as_mut_ptr();
+        core::ptr::copy_nonoverlapping(url.as_ptr(), dst, url.len());
+        let mut pos = url.len();
+        let mut separator = if url.contains('?') { b'&' } else { b'?' };
+        for (key, value) in pairs {
+            *dst.add(pos) = separator;
+            pos += 1;
+            pos += write_encoded(dst.add(pos), key.as_ref().as_bytes(), set);
+            *dst.add(pos) = b'=';
+            pos += 1;
+            pos += write_encoded(dst.add(pos), value.as_ref().as_bytes(), set);
+            separator = b'&';
+        }
+    }
+
+    result
+}
+
+/// Writes `text` percent-encoded with `set` to `dst`, returning the number of bytes written.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of [`encoded_len`] bytes.
+unsafe fn write_encoded(dst: *mut u8, text: &[u8], set: &EncodeSet) -> usize {
+    let mut pos = 0;
+    let mut run_start = 0;
+
+    for (x, &byte) in text.iter().enumerate() {
+        if !set.contains(byte) {
+            continue;
+        }
+
+        // Copy the run of bytes that didn't need encoding in one go.
+        let run = &text[run_start..x];
+        core::ptr::copy_nonoverlapping(run.as_ptr(), dst.add(pos), run.len());
+        pos += run.len();
+
+        *dst.add(pos) = b'%';
+        *dst.add(pos + 1) = HEX_DIGITS[(byte >> 4) as usize];
+        *dst.add(pos + 2) = HEX_DIGITS[(byte & 0xF) as usize];
+        pos += 3;
+        run_start = x + 1;
+    }
+
+    let run = &text[run_start..];
+    core::ptr::copy_nonoverlapping(run.as_ptr(), dst.add(pos), run.len());
+    pos + run.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", &PATH_SEGMENT, "")]
+    #[case("plain", &PATH_SEGMENT, "plain")]
+    #[case("a b", &PATH_SEGMENT, "a%20b")]
+    #[case("a/b", &PATH_SEGMENT, "a%2Fb")]
+    #[case("a/b", &PATH, "a/b")]
+    #[case("50%", &PATH_SEGMENT, "50%25")]
+    #[case("50%", &PATH, "50%")]
+    #[case("?#", &PATH, "%3F%23")]
+    #[case("?#", &QUERY, "?%23")]
+    #[case("a=b&c", &QUERY, "a=b&c")]
+    #[case("a=b&c", &COMPONENT, "a%3Db%26c")]
+    #[case("AZaz09-._~", &COMPONENT, "AZaz09-._~")]
+    #[case("\t\x7F", &CONTROLS, "%09%7F")]
+    #[case("日本", &EncodeSet::empty(), "%E6%97%A5%E6%9C%AC")]
+    fn test_encode(#[case] input: &str, #[case] set: &EncodeSet, #[case] expected: &str) {
+        let result = encode(input, set);
+        assert_eq!(result, expected, "Failed for input: {input:?}");
+        assert_eq!(encoded_len(input, set), expected.len());
+        assert_eq!(result.capacity(), result.len());
+    }
+
+    #[test]
+    fn test_encode_set_add_remove() {
+        let set = EncodeSet::empty().add(b'a');
+        assert!(set.contains(b'a'));
+        assert!(!set.contains(b'b'));
+        assert!(set.contains(0xFF));
+        assert!(!set.remove(b'a').contains(b'a'));
+        assert!(set.remove(0xFF).contains(0xFF));
+    }
+
+    #[test]
+    fn test_encode_set_non_ascii_leaves_ascii_bits() {
+        assert!(CONTROLS.remove(0xFF).contains(0x7F));
+        assert_eq!(CONTROLS.remove(0xFF), CONTROLS);
+        assert!(!EncodeSet::empty().add(0xC3).contains(b'C'));
+        assert_eq!(EncodeSet::empty().add(0xC3), EncodeSet::empty());
+    }
+
+    #[test]
+    fn test_encode_sets_ascii_bytes() {
+        for byte in 0..0x80u8 {
+            let unreserved = byte.is_ascii_alphanumeric() || b"-._~".contains(&byte);
+            assert_eq!(
+                COMPONENT.contains(byte),
+                !unreserved,
+                "Failed for byte: {byte}"
+            );
+            assert_eq!(CONTROLS.contains(byte), byte < 0x20 || byte == 0x7F);
+        }
+    }
+
+    #[rstest]
+    #[case("https://x.com", &[], "https://x.com")]
+    #[case("https://x.com/", &[], "https://x.com/")]
+    #[case("https://x.com//", &[], "https://x.com//")]
+    #[case("https://x.com/", &["a"], "https://x.com/a")]
+    #[case("https://x.com//", &["a"], "https://x.com//a")]
+    #[case("https://x.com", &["", "a"], "https://x.com//a")]
+    #[case("", &["a b", "c"], "/a%20b/c")]
+    #[case("/api", &["../etc", "x?y"], "/api/..%2Fetc/x%3Fy")]
+    fn test_join_slice(#[case] base: &str, #[case] segments: &[&str], #[case] expected: &str) {
+        let result = join_slice(base, segments, &PATH_SEGMENT);
+        assert_eq!(result, expected, "Failed for segments: {segments:?}");
+        assert_eq!(result.capacity(), result.len());
+    }
+
+    #[test]
+    fn test_join_n() {
+        let base = String::from("https://x.com/");
+        assert_eq!(join_2(&base, "a b", &PATH_SEGMENT), "https://x.com/a%20b");
+        assert_eq!(
+            join_3(&base, "a", "b/c", &PATH_SEGMENT),
+            "https://x.com/a/b%2Fc"
+        );
+        assert_eq!(
+            join_4(base, "a", String::from("b"), "c", &PATH),
+            "https://x.com/a/b/c"
+        );
+    }
+
+    #[test]
+    fn test_append_query() {
+        let empty: &[(&str, &str)] = &[];
+        assert_eq!(append_query("/a", empty, &COMPONENT), "/a");
+        assert_eq!(append_query("/a", &[("k", "")], &COMPONENT), "/a?k=");
+        assert_eq!(
+            append_query("/a?x=1", &[("k y", "v&w"), ("z", "1")], &COMPONENT),
+            "/a?x=1&k%20y=v%26w&z=1"
+        );
+
+        let result = append_query("/a", &[("q", "a b")], &QUERY);
+        assert_eq!(result, "/a?q=a%20b");
+        assert_eq!(result.capacity(), result.len());
+    }
+}
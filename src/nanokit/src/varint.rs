@@ -0,0 +1,360 @@
+use crate::count_bits::BitsNeeded;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Unsigned LEB128 variable-length integer encoding.
+///
+/// Each byte stores 7 bits of the value, lowest group first, with the high bit set on every
+/// byte except the last. Sizes are computed with [`BitsNeeded`], so no trial encoding is needed.
+///
+/// Implemented for all unsigned integer types, and for signed types wrapped in [`ZigZag`].
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::varint::Varint;
+/// let mut buffer = [0u8; 10];
+/// let written = 300u32.encode_into(&mut buffer).unwrap();
+/// assert_eq!(&buffer[..written], &[0xAC, 0x02]);
+/// assert_eq!(u32::decode(&buffer), Ok((300, 2)));
+/// ```
+pub trait Varint: Sized + Copy {
+    /// The maximum number of bytes an encoded value of this type can take.
+    const MAX_LEN: usize;
+
+    /// Returns the number of bytes the encoded value takes.
+    fn encoded_len(self) -> usize;
+
+    /// Writes the encoded value to `dst` without bounds checks, returning the number of
+    /// bytes written.
+    ///
+    /// # Safety
+    ///
+    /// `dst` must be valid for writes of [`Varint::encoded_len`] bytes.
+    unsafe fn encode_to_ptr(self, dst: *mut u8) -> usize;
+
+    /// Decodes a value from the start of `buffer`, returning it and the number of bytes read.
+    ///
+    /// Decoding is strict: encodings with redundant trailing zero groups, or that don't fit in
+    /// the type, are rejected rather than silently truncated.
+    fn decode(buffer: &[u8]) -> Result<(Self, usize), DecodeError>;
+
+    /// Writes the encoded value to the start of `buffer`, returning the number of bytes
+    /// written, or [`None`] if `buffer` is too short.
+    #[inline]
+    fn encode_into(self, buffer: &mut [u8]) -> Option<usize> {
+        let len = self.encoded_len();
+        if buffer.len() < len {
+            return None;
+        }
+
+        // SAFETY: `buffer` holds at least `len` bytes.
+        Some(unsafe { self.encode_to_ptr(buffer.as_mut_ptr()) })
+    }
+
+    /// Encodes the value into a new [`Vec`] of the exact length.
+    #[inline]
+    fn encode_to_vec(self) -> Vec<u8> {
+        let len = self.encoded_len();
+        let mut result: Vec<u8> = Vec::with_capacity(len);
+        unsafe {
+            self.encode_to_ptr(result.as_mut_ptr());
+            result.set_len(len);
+        }
+        result
+    }
+}
+
+/// Wraps a signed integer so it is encoded as a [`Varint`] using ZigZag encoding.
+///
+/// ZigZag maps small negative and positive numbers to small unsigned ones
+/// (`0, -1, 1, -2, ...` to `0, 1, 2, 3, ...`), so `-1` takes one byte rather than the maximum.
+///
+/// # Examples
+///
+/// ```
+/// use nanokit::varint::{Varint, ZigZag};
+/// assert_eq!(ZigZag(-1i64).encode_to_vec(), [0x01]);
+/// assert_eq!(ZigZag(63i64).encode_to_vec(), [0x7E]);
+/// assert_eq!(ZigZag::<i64>::decode(&[0x03]), Ok((ZigZag(-2), 1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ZigZag<T>(pub T);
+
+/// An error returned by [`Varint::decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The buffer ended before the last byte of the value.
+    Truncated,
+    /// The value has redundant trailing zero groups, so it is not in its shortest form.
+    Overlong,
+    /// The value does not fit in the target type.
+    Overflow,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecodeError::Truncated => "buffer ended inside a varint",
+            DecodeError::Overlong => "varint is not in its shortest form",
+            DecodeError::Overflow => "varint does not fit in the target type",
+        })
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Macro to implement the [`Varint`] trait for unsigned integer types.
+macro_rules! impl_varint {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Varint for $t {
+                const MAX_LEN: usize = (<$t>::BITS as usize).div_ceil(7);
+
+                #[inline]
+                fn encoded_len(self) -> usize {
+                    // 7 bits per byte; zero still takes one byte.
+                    (self.bits_needed_to_store().max(1) as usize).div_ceil(7)
+                }
+
+                #[inline]
+                unsafe fn encode_to_ptr(self, dst: *mut u8) -> usize {
+                    let mut value = self;
+                    let mut pos = 0;
+                    while value >= 0x80 {
+                        *dst.add(pos) = (value as u8) | 0x80;
+                        value >>= 7;
+                        pos += 1;
+                    }
+                    *dst.add(pos) = value as u8;
+                    pos + 1
+                }
+
+                fn decode(buffer: &[u8]) -> Result<(Self, usize), DecodeError> {
+                    let mut value: $t = 0;
+                    for (x, &byte) in buffer.iter().take(Self::MAX_LEN).enumerate() {
+                        let group = byte & 0x7F;
+                        if x == Self::MAX_LEN - 1 {
+                            // The last byte may only carry the bits left over, and can't continue.
+                            let spare_bits = <$t>::BITS - x as u32 * 7;
+                            if byte & 0x80 != 0 || (spare_bits < 7 && group >> spare_bits != 0) {
+                                return Err(DecodeError::Overflow);
+                            }
+                        }
+
+                        value |= (group as $t) << (x * 7);
+                        if byte & 0x80 == 0 {
+                            if byte == 0 && x > 0 {
+                                return Err(DecodeError::Overlong);
+                            }
+                            return Ok((value, x + 1));
+                        }
+                    }
+
+                    Err(DecodeError::Truncated)
+                }
+            }
+        )*
+    }
+}
+
+impl_varint!(u8, u16, u32, u64, u128, usize);
+
+/// Macro to implement the [`Varint`] trait for [`ZigZag`]-wrapped signed integer types.
+///
+/// # Parameters
+///
+/// * `$t`: The signed type (e.g. `i32`).
+/// * `$u`: The unsigned type of the same width (e.g. `u32`).
+macro_rules! impl_zigzag_varint {
+    ($($t:ty => $u:ty),* $(,)?) => {
+        $(
+            impl ZigZag<$t> {
+                /// Returns the ZigZag-encoded value.
+                #[inline]
+                pub const fn to_unsigned(self) -> $u {
+                    ((self.0 << 1) ^ (self.0 >> (<$t>::BITS - 1))) as $u
+                }
+
+                /// Creates a value from its ZigZag encoding.
+                #[inline]
+                pub const fn from_unsigned(value: $u) -> Self {
+                    Self((value >> 1) as $t ^ -((value & 1) as $t))
+                }
+            }
+
+            impl Varint for ZigZag<$t> {
+                const MAX_LEN: usize = <$u as Varint>::MAX_LEN;
+
+                #[inline]
+                fn encoded_len(self) -> usize {
+                    self.to_unsigned().encoded_len()
+                }
+
+                #[inline]
+                unsafe fn encode_to_ptr(self, dst: *mut u8) -> usize {
+                    self.to_unsigned().encode_to_ptr(dst)
+                }
+
+                #[inline]
+                fn decode(buffer: &[u8]) -> Result<(Self, usize), DecodeError> {
+                    <$u>::decode(buffer).map(|(value, len)| (Self::from_unsigned(value), len))
+                }
+            }
+        )*
+    }
+}
+
+impl_zigzag_varint! {
+    i8 => u8,
+    i16 => u16,
+    i32 => u32,
+    i64 => u64,
+    i128 => u128,
+    isize => usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, &[0x00])]
+    #[case(1, &[0x01])]
+    #[case(127, &[0x7F])]
+    #[case(128, &[0x80, 0x01])]
+    #[case(300, &[0xAC, 0x02])]
+    #[case(16384, &[0x80, 0x80, 0x01])]
+    #[case(u32::MAX, &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F])]
+    fn test_encode_u32(#[case] value: u32, #[case] expected: &[u8]) {
+        assert_eq!(
+            value.encoded_len(),
+            expected.len(),
+            "Failed for input: {value:?}"
+        );
+        assert_eq!(
+            value.encode_to_vec(),
+            expected,
+            "Failed for input: {value:?}"
+        );
+        assert_eq!(u32::decode(expected), Ok((value, expected.len())));
+    }
+
+    #[test]
+    fn test_max_len() {
+        assert_eq!(u8::MAX_LEN, 2);
+        assert_eq!(u16::MAX_LEN, 3);
+        assert_eq!(u32::MAX_LEN, 5);
+        assert_eq!(u64::MAX_LEN, 10);
+        assert_eq!(u128::MAX_LEN, 19);
+        assert_eq!(u8::MAX.encoded_len(), u8::MAX_LEN);
+        assert_eq!(u16::MAX.encoded_len(), u16::MAX_LEN);
+        assert_eq!(u32::MAX.encoded_len(), u32::MAX_LEN);
+        assert_eq!(u64::MAX.encoded_len(), u64::MAX_LEN);
+        assert_eq!(u128::MAX.encoded_len(), u128::MAX_LEN);
+        assert_eq!(ZigZag(i64::MIN).encoded_len(), ZigZag::<i64>::MAX_LEN);
+    }
+
+    #[test]
+    fn test_round_trip_all_u8_u16() {
+        for value in 0..=u16::MAX {
+            let encoded = value.encode_to_vec();
+            assert_eq!(u16::decode(&encoded), Ok((value, encoded.len())));
+            if let Ok(byte) = u8::try_from(value) {
+                assert_eq!(byte.encode_to_vec(), encoded);
+                assert_eq!(u8::decode(&encoded), Ok((byte, encoded.len())));
+            } else {
+                assert_eq!(u8::decode(&encoded), Err(DecodeError::Overflow));
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_powers_of_two() {
+        for shift in 0..128 {
+            for value in [1u128 << shift, (1u128 << shift) - 1, (1 << shift) + 1] {
+                let encoded = value.encode_to_vec();
+                assert_eq!(u128::decode(&encoded), Ok((value, encoded.len())));
+                if let Ok(value) = u64::try_from(value) {
+                    assert_eq!(u64::decode(&encoded), Ok((value, encoded.len())));
+                } else {
+                    assert_eq!(u64::decode(&encoded), Err(DecodeError::Overflow));
+                }
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(&[], DecodeError::Truncated)]
+    #[case(&[0x80], DecodeError::Truncated)]
+    #[case(&[0xFF, 0xFF], DecodeError::Truncated)]
+    #[case(&[0x80, 0x00], DecodeError::Overlong)]
+    #[case(&[0xFF, 0x80, 0x00], DecodeError::Overlong)]
+    #[case(&[0xFF, 0xFF, 0xFF, 0xFF, 0x10], DecodeError::Overflow)]
+    #[case(&[0xFF, 0xFF, 0xFF, 0xFF, 0x8F, 0x00], DecodeError::Overflow)]
+    #[case(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x01], DecodeError::Overflow)]
+    fn test_decode_errors_u32(#[case] input: &[u8], #[case] expected: DecodeError) {
+        assert_eq!(
+            u32::decode(input),
+            Err(expected),
+            "Failed for input: {input:?}"
+        );
+    }
+
+    #[test]
+    fn test_decode_ignores_trailing_bytes() {
+        assert_eq!(u64::decode(&[0xAC, 0x02, 0xFF, 0xFF]), Ok((300, 2)));
+        assert_eq!(u64::decode(&[0x00, 0x00]), Ok((0, 1)));
+    }
+
+    #[test]
+    fn test_encode_into() {
+        let mut buffer = [0xAAu8; 3];
+        assert_eq!(300u64.encode_into(&mut buffer), Some(2));
+        assert_eq!(buffer, [0xAC, 0x02, 0xAA]);
+        assert_eq!(u64::MAX.encode_into(&mut buffer), None);
+        assert_eq!(buffer, [0xAC, 0x02, 0xAA]);
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(-1, 1)]
+    #[case(1, 2)]
+    #[case(-2, 3)]
+    #[case(i32::MAX, u32::MAX - 1)]
+    #[case(i32::MIN, u32::MAX)]
+    fn test_zigzag_mapping(#[case] value: i32, #[case] expected: u32) {
+        assert_eq!(
+            ZigZag(value).to_unsigned(),
+            expected,
+            "Failed for input: {value:?}"
+        );
+        assert_eq!(ZigZag::<i32>::from_unsigned(expected), ZigZag(value));
+    }
+
+    #[test]
+    fn test_zigzag_round_trip_all_i16() {
+        for value in i16::MIN..=i16::MAX {
+            let encoded = ZigZag(value).encode_to_vec();
+            assert_eq!(encoded.len(), ZigZag(value).encoded_len());
+            assert_eq!(
+                ZigZag::<i16>::decode(&encoded),
+                Ok((ZigZag(value), encoded.len()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_zigzag_extremes() {
+        for value in [i128::MIN, i128::MAX, 0, -1] {
+            let encoded = ZigZag(value).encode_to_vec();
+            assert_eq!(
+                ZigZag::<i128>::decode(&encoded),
+                Ok((ZigZag(value), encoded.len()))
+            );
+        }
+        assert_eq!(ZigZag(-64i8).encode_to_vec(), [0x7F]);
+        assert_eq!(ZigZag(64i8).encode_to_vec(), [0x80, 0x01]);
+    }
+}